@@ -0,0 +1,232 @@
+use std::path::Path;
+
+//================================================================
+
+pub const HELP: &str = r#"Usage: engine [OPTION]... [PATH] [-- ARGUMENT...]
+       engine meta [OPTION]...
+       engine pack [OPTION]... [FOLDER]
+
+Run a Flak game. PATH is either a game folder, or a game ZIP archive, and
+defaults to the working directory. Every ARGUMENT after "--" is passed to
+Lua as "flak.argument".
+
+Option:
+    --main MODULE   Entry module to require. Must be in the "main" folder when
+                    running a ZIP archive. Default: "main/main".
+    --log           Enable Raylib logging.
+    --windowed      Disable full-screen mode.
+    --headless      Run without a window or audio device.
+    --debug PORT    Wait for a Debug Adapter Protocol client on a local port.
+    --scale WxH     Window scale, such as 1280x720.
+    --help          Show this message.
+
+Command:
+    meta            Write the meta, declaration and API reference files.
+                    Use "meta --help" for every option.
+    pack            Pack a game into a distributable bundle.
+                    Use "pack --help" for every option."#;
+
+#[derive(Clone)]
+pub struct Argument {
+    // Entry module.
+    pub main: String,
+    // Path to the "main" ZIP archive.
+    pub file: String,
+    // Argument list to pass to Lua.
+    pub list: Vec<String>,
+    pub log: bool,
+    pub windowed: bool,
+    pub headless: bool,
+    pub scale: Option<(i32, i32)>,
+    pub debug: Option<u16>,
+}
+
+impl Argument {
+    pub const MAIN_PATH: &str = "main/main";
+    pub const MAIN_FILE: &str = "main";
+
+    // Parse every command-line argument. Returns none if "--help" was given, in which case no game should be run.
+    pub fn new() -> anyhow::Result<Option<Self>> {
+        Self::from_list(std::env::args().skip(1))
+    }
+
+    // Parse every argument after the executable itself.
+    fn from_list(mut list: impl Iterator<Item = String>) -> anyhow::Result<Option<Self>> {
+        let mut argument = Self {
+            main: Self::MAIN_PATH.to_string(),
+            file: Self::MAIN_FILE.to_string(),
+            list: Vec::new(),
+            log: false,
+            windowed: false,
+            headless: false,
+            scale: None,
+            debug: None,
+        };
+        let mut path = None;
+
+        while let Some(token) = list.next() {
+            match token.as_str() {
+                "--" => {
+                    argument.list.extend(list.by_ref());
+                }
+                "--main" => {
+                    argument.main = Self::get_value(&mut list, &token)?;
+                }
+                "--log" => argument.log = true,
+                "--windowed" => argument.windowed = true,
+                "--headless" => argument.headless = true,
+                "--scale" => {
+                    let value = Self::get_value(&mut list, &token)?;
+                    argument.scale = Some(Self::get_scale(&value)?);
+                }
+                "--debug" => {
+                    let value = Self::get_value(&mut list, &token)?;
+                    argument.debug = Some(value.parse().map_err(|_| {
+                        anyhow::anyhow!("Invalid port \"{value}\", expected a port such as 4711.")
+                    })?);
+                }
+                "--help" => return Ok(None),
+                _ if token.starts_with("--") => {
+                    return Err(anyhow::anyhow!(
+                        "Unknown option \"{token}\". Use \"--help\" for a list of every option."
+                    ));
+                }
+                _ => {
+                    if path.is_some() {
+                        return Err(anyhow::anyhow!(
+                            "Unexpected argument \"{token}\". Use \"--\" to pass an argument to Lua."
+                        ));
+                    }
+
+                    path = Some(token);
+                }
+            }
+        }
+
+        if let Some(path) = path {
+            argument.set_path(Path::new(&path))?;
+        }
+
+        Ok(Some(argument))
+    }
+
+    // Move into the game folder, so that every relative path in the game resolves as usual.
+    fn set_path(&mut self, path: &Path) -> anyhow::Result<()> {
+        if path.is_dir() {
+            std::env::set_current_dir(path)?;
+        } else if path.is_file() {
+            if let Some(folder) = path.parent()
+                && !folder.as_os_str().is_empty()
+            {
+                std::env::set_current_dir(folder)?;
+            }
+
+            if let Some(file) = path.file_name() {
+                self.file = file.to_string_lossy().to_string();
+            }
+        } else {
+            return Err(anyhow::anyhow!(
+                "Path \"{}\" is not a game folder or a game ZIP archive.",
+                path.display()
+            ));
+        }
+
+        Ok(())
+    }
+
+    fn get_value(list: &mut impl Iterator<Item = String>, name: &str) -> anyhow::Result<String> {
+        list.next()
+            .ok_or_else(|| anyhow::anyhow!("Missing value for option \"{name}\"."))
+    }
+
+    fn get_scale(value: &str) -> anyhow::Result<(i32, i32)> {
+        if let Some((x, y)) = value.split_once('x')
+            && let Ok(x) = x.parse()
+            && let Ok(y) = y.parse()
+            && x > 0
+            && y > 0
+        {
+            Ok((x, y))
+        } else {
+            Err(anyhow::anyhow!(
+                "Invalid scale \"{value}\", expected a positive scale such as 1280x720."
+            ))
+        }
+    }
+}
+
+//================================================================
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn get_argument(list: &[&str]) -> anyhow::Result<Argument> {
+        Argument::from_list(list.iter().map(|value| value.to_string()))
+            .map(|argument| argument.expect("no \"--help\" option"))
+    }
+
+    #[test]
+    fn default() {
+        let argument = get_argument(&[]).unwrap();
+
+        assert_eq!(argument.main, Argument::MAIN_PATH);
+        assert_eq!(argument.file, Argument::MAIN_FILE);
+        assert!(argument.list.is_empty());
+        assert!(!argument.log && !argument.windowed && !argument.headless);
+        assert_eq!(argument.scale, None);
+        assert_eq!(argument.debug, None);
+    }
+
+    #[test]
+    fn option() {
+        let argument = get_argument(&[
+            "--main",
+            "test/main",
+            "--log",
+            "--windowed",
+            "--headless",
+            "--scale",
+            "1280x720",
+            "--debug",
+            "4711",
+            "--",
+            "a",
+            "--b",
+        ])
+        .unwrap();
+
+        assert_eq!(argument.main, "test/main");
+        assert_eq!(argument.list, ["a", "--b"]);
+        assert!(argument.log && argument.windowed && argument.headless);
+        assert_eq!(argument.scale, Some((1280, 720)));
+        assert_eq!(argument.debug, Some(4711));
+    }
+
+    #[test]
+    fn help() {
+        let list = ["--log", "--help", "--unknown"].map(String::from);
+
+        assert!(Argument::from_list(list.into_iter()).unwrap().is_none());
+    }
+
+    #[test]
+    fn invalid() {
+        assert!(get_argument(&["--unknown"]).is_err());
+        assert!(get_argument(&["--main"]).is_err());
+        assert!(get_argument(&["--debug", "port"]).is_err());
+        assert!(get_argument(&["--scale", "1280"]).is_err());
+    }
+
+    #[test]
+    fn scale() {
+        assert_eq!(Argument::get_scale("1280x720").unwrap(), (1280, 720));
+        assert!(Argument::get_scale("-1x0").is_err());
+        assert!(Argument::get_scale("0x720").is_err());
+        assert!(Argument::get_scale("1280x-720").is_err());
+        assert!(Argument::get_scale("1280").is_err());
+        assert!(Argument::get_scale("1280x").is_err());
+        assert!(Argument::get_scale("ax720").is_err());
+        assert!(Argument::get_scale("1280X720").is_err());
+    }
+}
@@ -0,0 +1,287 @@
+use crate::module::font::*;
+use raylib::prelude::*;
+
+//================================================================
+
+pub struct Console {
+    active: bool,
+    // Current input line.
+    input: String,
+    // Previous input lines, and the currently selected one.
+    history: Vec<String>,
+    history_index: Option<usize>,
+    // Output lines, and the scroll offset from the bottom.
+    output: Vec<String>,
+    scroll: usize,
+}
+
+impl Console {
+    const KEY_TOGGLE: KeyboardKey = KeyboardKey::KEY_GRAVE;
+    const TEXT_SIZE: i32 = 20;
+    const TEXT_GAP: i32 = 4;
+    const OUTPUT_LIMIT: usize = 1024;
+    const HISTORY_LIMIT: usize = 256;
+    const COLOR_BACK: Color = Color::new(0, 0, 0, 224);
+    const COLOR_TEXT: Color = Color::WHITE;
+
+    pub fn new() -> Self {
+        Self {
+            active: false,
+            input: String::new(),
+            history: Vec::new(),
+            history_index: None,
+            output: Vec::new(),
+            scroll: 0,
+        }
+    }
+
+    // Check if the console is open, in which case every key-board input goes to the console rather than the game.
+    pub fn is_active(lua: &mlua::Lua) -> bool {
+        lua.app_data_ref::<Self>()
+            .map(|console| console.active)
+            .unwrap_or_default()
+    }
+
+    // Add a line of output to the scroll-back. Any NUL character is replaced, as it can not be drawn.
+    pub fn push(&mut self, text: &str) {
+        for line in text.lines() {
+            self.output.push(line.replace('\0', "\u{FFFD}"));
+        }
+
+        if self.output.len() > Self::OUTPUT_LIMIT {
+            self.output.drain(0..self.output.len() - Self::OUTPUT_LIMIT);
+        }
+
+        self.scroll = 0;
+    }
+
+    // Handle input and draw the console. Must be called within a draw session.
+    pub fn update(lua: &mlua::Lua) -> mlua::Result<()> {
+        // Borrow the console only for input handling, as running a command will run Lua code.
+        let command = if let Some(mut console) = lua.app_data_mut::<Self>() {
+            if unsafe { ffi::IsKeyPressed(Self::KEY_TOGGLE as i32) } {
+                console.active = !console.active;
+                // Discard the toggle key character.
+                while unsafe { ffi::GetCharPressed() } != 0 {}
+            }
+
+            if !console.active {
+                return Ok(());
+            }
+
+            console.get_input(lua)
+        } else {
+            return Ok(());
+        };
+
+        if let Some(command) = command {
+            let result = Self::run(lua, &command);
+
+            if let Some(mut console) = lua.app_data_mut::<Self>() {
+                console.push(&format!("> {command}"));
+
+                match result {
+                    Ok(result) if !result.is_empty() => console.push(&result),
+                    Ok(_) => {}
+                    Err(error) => console.push(&error.to_string()),
+                }
+            }
+        }
+
+        if let Some(console) = lua.app_data_ref::<Self>() {
+            console.draw()?;
+        }
+
+        Ok(())
+    }
+
+    // Handle a frame of key-board input, and return the input line if it was submitted.
+    fn get_input(&mut self, lua: &mlua::Lua) -> Option<String> {
+        unsafe {
+            loop {
+                let code = ffi::GetCharPressed();
+
+                if code == 0 {
+                    break;
+                }
+
+                if let Some(code) = char::from_u32(code as u32) {
+                    self.input.push(code);
+                }
+            }
+
+            if Self::is_press(KeyboardKey::KEY_BACKSPACE) {
+                self.input.pop();
+            }
+
+            if Self::is_press(KeyboardKey::KEY_UP) && !self.history.is_empty() {
+                let index = match self.history_index {
+                    Some(index) => index.saturating_sub(1),
+                    None => self.history.len() - 1,
+                };
+
+                self.history_index = Some(index);
+                self.input = self.history[index].clone();
+            }
+
+            if Self::is_press(KeyboardKey::KEY_DOWN)
+                && let Some(index) = self.history_index
+            {
+                if index + 1 < self.history.len() {
+                    self.history_index = Some(index + 1);
+                    self.input = self.history[index + 1].clone();
+                } else {
+                    self.history_index = None;
+                    self.input.clear();
+                }
+            }
+
+            if Self::is_press(KeyboardKey::KEY_PAGE_UP) {
+                self.scroll = (self.scroll + 1).min(self.output.len().saturating_sub(1));
+            }
+
+            if Self::is_press(KeyboardKey::KEY_PAGE_DOWN) {
+                self.scroll = self.scroll.saturating_sub(1);
+            }
+
+            if Self::is_press(KeyboardKey::KEY_TAB) {
+                self.complete(lua);
+            }
+
+            if ffi::IsKeyPressed(KeyboardKey::KEY_ENTER as i32) && !self.input.is_empty() {
+                let input = std::mem::take(&mut self.input);
+
+                if self.history.last() != Some(&input) {
+                    self.history.push(input.clone());
+                }
+
+                if self.history.len() > Self::HISTORY_LIMIT {
+                    self.history.remove(0);
+                }
+
+                self.history_index = None;
+
+                return Some(input);
+            }
+        }
+
+        None
+    }
+
+    // Evaluate a command, as an expression first, and as a statement otherwise.
+    fn run(lua: &mlua::Lua, command: &str) -> mlua::Result<String> {
+        let result = match lua
+            .load(format!("return {command}"))
+            .set_name("=console")
+            .into_function()
+        {
+            Ok(call) => call.call::<mlua::MultiValue>(())?,
+            Err(_) => lua
+                .load(command)
+                .set_name("=console")
+                .call::<mlua::MultiValue>(())?,
+        };
+
+        let result: Vec<String> = result
+            .iter()
+            .map(|value| value.to_string().unwrap_or_else(|_| format!("{value:?}")))
+            .collect();
+
+        Ok(result.join("\t"))
+    }
+
+    // Complete the last path in the input line, looking it up from the global table.
+    fn complete(&mut self, lua: &mlua::Lua) {
+        // Start right after the last delimiter, which may be more than one byte long.
+        let start = self
+            .input
+            .char_indices()
+            .rfind(|(_, c)| !(c.is_alphanumeric() || *c == '_' || *c == '.' || *c == ':'))
+            .map(|(index, c)| index + c.len_utf8())
+            .unwrap_or(0);
+        let path = self.input[start..].to_string();
+        let (parent, name) = match path.rfind(['.', ':']) {
+            Some(index) => (&path[..index], &path[index + 1..]),
+            None => ("", path.as_str()),
+        };
+
+        let mut table = lua.globals();
+
+        for key in parent.split(['.', ':']).filter(|key| !key.is_empty()) {
+            match table.get::<mlua::Value>(key) {
+                Ok(mlua::Value::Table(value)) => table = value,
+                _ => return,
+            }
+        }
+
+        let mut list: Vec<String> = table
+            .pairs::<mlua::Value, mlua::Value>()
+            .flatten()
+            .filter_map(|(key, _)| key.as_string().map(|key| key.to_string_lossy()))
+            .filter(|key| key.starts_with(name))
+            .collect();
+
+        list.sort();
+
+        let Some(first) = list.first() else {
+            return;
+        };
+
+        // Complete up to the longest prefix shared by every candidate.
+        let mut prefix = first.clone();
+
+        for entry in &list[1..] {
+            while !entry.starts_with(&prefix) {
+                prefix.pop();
+            }
+        }
+
+        if list.len() > 1 {
+            self.push(&list.join("  "));
+        }
+
+        self.input.truncate(start + path.len() - name.len());
+        self.input.push_str(&prefix);
+    }
+
+    fn draw(&self) -> mlua::Result<()> {
+        let (scale_x, scale_y) = unsafe { (ffi::GetScreenWidth(), ffi::GetScreenHeight() / 2) };
+        let line = Self::TEXT_SIZE + Self::TEXT_GAP;
+
+        unsafe {
+            ffi::DrawRectangle(0, 0, scale_x, scale_y, Self::COLOR_BACK.into());
+        }
+
+        let mut y = scale_y - line;
+
+        Self::draw_line(&format!("> {}_", self.input), y)?;
+
+        for text in self.output.iter().rev().skip(self.scroll) {
+            y -= line;
+
+            if y < 0 {
+                break;
+            }
+
+            Self::draw_line(text, y)?;
+        }
+
+        Ok(())
+    }
+
+    fn draw_line(text: &str, y: i32) -> mlua::Result<()> {
+        draw_text(
+            None,
+            text,
+            Vector2::new(Self::TEXT_GAP as f32, y as f32),
+            Self::TEXT_SIZE as f32,
+            // Same spacing as the raylib DrawText function, for the default font.
+            (Self::TEXT_SIZE / 10) as f32,
+            Self::COLOR_TEXT,
+        )
+    }
+
+    fn is_press(key: KeyboardKey) -> bool {
+        unsafe { ffi::IsKeyPressed(key as i32) || ffi::IsKeyPressedRepeat(key as i32) }
+    }
+}
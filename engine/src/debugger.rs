@@ -0,0 +1,823 @@
+use serde_json::Value as Json;
+use serde_json::json;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::io::BufRead;
+use std::io::BufReader;
+use std::io::Read;
+use std::io::Write;
+use std::net::TcpListener;
+use std::net::TcpStream;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::OnceLock;
+use std::sync::atomic::AtomicI64;
+use std::sync::atomic::Ordering;
+use std::sync::mpsc::Receiver;
+use std::sync::mpsc::Sender;
+
+//================================================================
+
+// The server out-lives every script, so that a client can stay attached across a script re-load.
+static SERVER: OnceLock<Debugger> = OnceLock::new();
+
+pub struct Debugger {
+    receive: Mutex<Receiver<Json>>,
+    stream: Arc<Mutex<Option<TcpStream>>>,
+    state: Mutex<DebuggerState>,
+    sequence: AtomicI64,
+}
+
+struct DebuggerState {
+    // Line break-point list, by chunk name.
+    breakpoint: HashMap<String, HashSet<usize>>,
+    mode: DebuggerMode,
+    // True once the client has finished the initial configuration.
+    ready: bool,
+}
+
+#[derive(Clone, Copy)]
+enum DebuggerMode {
+    Continue,
+    Pause,
+    StepIn,
+    StepOver(usize),
+    StepOut(usize),
+}
+
+// Variable scope handed out to the client while stopped, index is the variable reference.
+enum DebuggerScope {
+    Local(usize),
+    Upvalue(usize),
+    Table(mlua::Table),
+}
+
+impl Debugger {
+    const THREAD_ID: i64 = 1;
+    // Every request that is only handled while stopped.
+    const STOP_COMMAND: [&str; 8] = [
+        "continue",
+        "next",
+        "stepIn",
+        "stepOut",
+        "stackTrace",
+        "scopes",
+        "variables",
+        "evaluate",
+    ];
+    const HOOK_NAME: &str = "flak_debugger";
+    // Lua functions are called from the hook, which adds two levels (the hook and the function itself) to the stack.
+    const LEVEL_OFFSET: usize = 2;
+    const HOOK_CODE: &str = r#"
+local debug = debug
+local hook = {}
+
+function hook.get_local(level)
+    local list = {}
+    local i = 1
+
+    while true do
+        local name, value = debug.getlocal(level, i)
+
+        if not name then
+            break
+        end
+
+        -- Skip internal variables, such as "(for index)".
+        if name:sub(1, 1) ~= "(" then
+            list[#list + 1] = { name, value }
+        end
+
+        i = i + 1
+    end
+
+    return list
+end
+
+function hook.get_upvalue(level)
+    local list = {}
+    local info = debug.getinfo(level, "f")
+    local i = 1
+
+    while info do
+        local name, value = debug.getupvalue(info.func, i)
+
+        if not name then
+            break
+        end
+
+        list[#list + 1] = { name, value }
+        i = i + 1
+    end
+
+    return list
+end
+
+function hook.evaluate(level, code)
+    local scope = {}
+    local info = debug.getinfo(level, "f")
+    local i = 1
+
+    while info do
+        local name, value = debug.getupvalue(info.func, i)
+
+        if not name then
+            break
+        end
+
+        scope[name] = value
+        i = i + 1
+    end
+
+    i = 1
+
+    while true do
+        local name, value = debug.getlocal(level, i)
+
+        if not name then
+            break
+        end
+
+        scope[name] = value
+        i = i + 1
+    end
+
+    setmetatable(scope, { __index = _G })
+
+    local call, error = load("return " .. code, "=evaluate", "t", scope)
+
+    if not call then
+        call, error = load(code, "=evaluate", "t", scope)
+    end
+
+    if not call then
+        return false, error
+    end
+
+    return pcall(call)
+end
+
+return hook
+"#;
+
+    // Attach the debugger to a Lua state, starting the server on the first call.
+    pub fn attach(lua: &mlua::Lua, port: u16) -> anyhow::Result<()> {
+        if lua.globals().get::<Option<mlua::Table>>("debug")?.is_none() {
+            return Err(anyhow::anyhow!(
+                "The debugger requires the Lua debug library, which is not available in safe mode."
+            ));
+        }
+
+        let hook: mlua::Table = lua.load(Self::HOOK_CODE).set_name("=debugger").eval()?;
+        lua.set_named_registry_value(Self::HOOK_NAME, hook)?;
+
+        let server = if let Some(server) = SERVER.get() {
+            server
+        } else {
+            let server = Self::new(port)?;
+            SERVER.get_or_init(|| server)
+        };
+
+        // Wait for the client to set every break-point before running any code.
+        while !server.get_state().ready {
+            let request = server.get_receive().recv()?;
+            server.handle(lua, &request, None)?;
+        }
+
+        lua.set_global_hook(mlua::HookTriggers::EVERY_LINE, |lua, debug| {
+            if let Some(server) = SERVER.get() {
+                server.hook(lua, debug)?;
+            }
+
+            Ok(mlua::VmState::Continue)
+        })?;
+
+        Ok(())
+    }
+
+    fn new(port: u16) -> anyhow::Result<Self> {
+        let listener = TcpListener::bind(("127.0.0.1", port))?;
+        let stream: Arc<Mutex<Option<TcpStream>>> = Arc::new(Mutex::new(None));
+        let (send, receive) = std::sync::mpsc::channel();
+
+        eprintln!("Waiting for a debugger on 127.0.0.1:{port}.");
+
+        let stream_listen = stream.clone();
+
+        std::thread::spawn(move || {
+            for client in listener.incoming().flatten() {
+                if let Ok(clone) = client.try_clone() {
+                    *stream_listen.lock().unwrap() = Some(clone);
+
+                    Self::read(client, &send);
+                }
+            }
+        });
+
+        Ok(Self::from_receive(receive, stream))
+    }
+
+    fn from_receive(receive: Receiver<Json>, stream: Arc<Mutex<Option<TcpStream>>>) -> Self {
+        Self {
+            receive: Mutex::new(receive),
+            stream,
+            state: Mutex::new(DebuggerState {
+                breakpoint: HashMap::new(),
+                mode: DebuggerMode::Continue,
+                ready: false,
+            }),
+            sequence: AtomicI64::new(1),
+        }
+    }
+
+    // Read every message from a client, until it disconnects.
+    fn read(client: impl Read, send: &Sender<Json>) {
+        let mut reader = BufReader::new(client);
+
+        loop {
+            let mut length = None;
+
+            loop {
+                let mut line = String::new();
+
+                if reader.read_line(&mut line).unwrap_or(0) == 0 {
+                    // Resume the game if the client is gone.
+                    let _ =
+                        send.send(json!({ "type": "request", "seq": 0, "command": "disconnect" }));
+                    return;
+                }
+
+                let line = line.trim();
+
+                if line.is_empty() {
+                    break;
+                }
+
+                if let Some(value) = line.strip_prefix("Content-Length:") {
+                    length = value.trim().parse::<usize>().ok();
+                }
+            }
+
+            let Some(length) = length else {
+                continue;
+            };
+
+            let mut buffer = vec![0; length];
+
+            if reader.read_exact(&mut buffer).is_err() {
+                continue;
+            }
+
+            if let Ok(message) = serde_json::from_slice(&buffer) {
+                let _ = send.send(message);
+            }
+        }
+    }
+
+    //================================================================
+
+    fn hook(&self, lua: &mlua::Lua, debug: &mlua::Debug) -> mlua::Result<()> {
+        // Handle any request sent while running, such as a pause or a new break-point.
+        loop {
+            let request = self.get_receive().try_recv();
+
+            if let Ok(request) = request {
+                self.handle(lua, &request, None)?;
+            } else {
+                break;
+            }
+        }
+
+        let source = debug.source();
+
+        if source.what == "C" {
+            return Ok(());
+        }
+
+        let Some(line) = debug.current_line() else {
+            return Ok(());
+        };
+
+        let mode = self.get_state().mode;
+
+        let reason = match mode {
+            DebuggerMode::Continue => {
+                let file = Self::get_source_name(&source.source.unwrap_or_default());
+                let state = self.get_state();
+
+                if let Some(list) = state.breakpoint.get(&file)
+                    && list.contains(&line)
+                {
+                    Some("breakpoint")
+                } else {
+                    None
+                }
+            }
+            DebuggerMode::Pause => Some("pause"),
+            DebuggerMode::StepIn => Some("step"),
+            DebuggerMode::StepOver(depth) => (Self::get_depth(lua) <= depth).then_some("step"),
+            DebuggerMode::StepOut(depth) => (Self::get_depth(lua) < depth).then_some("step"),
+        };
+
+        if let Some(reason) = reason {
+            self.stop(lua, reason)?;
+        }
+
+        Ok(())
+    }
+
+    // Block the game until the client resumes it.
+    fn stop(&self, lua: &mlua::Lua, reason: &str) -> mlua::Result<()> {
+        self.get_state().mode = DebuggerMode::Continue;
+
+        self.event(
+            "stopped",
+            json!({ "reason": reason, "threadId": Self::THREAD_ID, "allThreadsStopped": true }),
+        );
+
+        let mut scope = Vec::new();
+
+        loop {
+            let request = self.get_receive().recv();
+
+            let Ok(request) = request else {
+                return Ok(());
+            };
+
+            if self.handle(lua, &request, Some(&mut scope))? {
+                return Ok(());
+            }
+        }
+    }
+
+    // Handle a client request. Return true if the game should resume.
+    fn handle(
+        &self,
+        lua: &mlua::Lua,
+        request: &Json,
+        scope: Option<&mut Vec<DebuggerScope>>,
+    ) -> mlua::Result<bool> {
+        // Stack inspection is only possible while stopped.
+        let stop = scope.is_some();
+        let mut empty = Vec::new();
+        let scope = scope.unwrap_or(&mut empty);
+        let argument = &request["arguments"];
+
+        match request["command"].as_str().unwrap_or_default() {
+            "initialize" => {
+                self.respond(
+                    request,
+                    json!({
+                        "supportsConfigurationDoneRequest": true,
+                        "supportsEvaluateForHovers": true,
+                    }),
+                );
+                self.event("initialized", json!({}));
+            }
+            "launch" | "attach" | "setExceptionBreakpoints" => {
+                self.respond(request, json!({}));
+            }
+            "configurationDone" => {
+                self.get_state().ready = true;
+                self.respond(request, json!({}));
+            }
+            "setBreakpoints" => {
+                let path = argument["source"]["path"].as_str().unwrap_or_default();
+                let path = Self::get_path_name(path);
+                let mut list = HashSet::new();
+                let mut result = Vec::new();
+
+                if let Some(breakpoint) = argument["breakpoints"].as_array() {
+                    for entry in breakpoint {
+                        if let Some(line) = entry["line"].as_u64() {
+                            list.insert(line as usize);
+                            result.push(json!({ "verified": true, "line": line }));
+                        }
+                    }
+                }
+
+                self.get_state().breakpoint.insert(path, list);
+                self.respond(request, json!({ "breakpoints": result }));
+            }
+            "threads" => {
+                self.respond(
+                    request,
+                    json!({ "threads": [{ "id": Self::THREAD_ID, "name": "main" }] }),
+                );
+            }
+            "pause" => {
+                self.get_state().mode = DebuggerMode::Pause;
+                self.respond(request, json!({}));
+            }
+            "disconnect" => {
+                let mut state = self.get_state();
+                state.breakpoint.clear();
+                state.mode = DebuggerMode::Continue;
+                state.ready = true;
+                drop(state);
+
+                self.respond(request, json!({}));
+
+                return Ok(true);
+            }
+            "continue" | "next" | "stepIn" | "stepOut" if stop => {
+                let depth = Self::get_depth(lua);
+
+                self.get_state().mode = match request["command"].as_str() {
+                    Some("next") => DebuggerMode::StepOver(depth),
+                    Some("stepIn") => DebuggerMode::StepIn,
+                    Some("stepOut") => DebuggerMode::StepOut(depth),
+                    _ => DebuggerMode::Continue,
+                };
+
+                self.respond(request, json!({ "allThreadsContinued": true }));
+
+                return Ok(true);
+            }
+            "stackTrace" if stop => {
+                self.respond(request, json!({ "stackFrames": Self::get_frame(lua) }));
+            }
+            "scopes" if stop => {
+                let frame = argument["frameId"].as_u64().unwrap_or_default() as usize;
+
+                scope.push(DebuggerScope::Local(frame));
+                let local = scope.len();
+                scope.push(DebuggerScope::Upvalue(frame));
+                let upvalue = scope.len();
+
+                self.respond(
+                    request,
+                    json!({ "scopes": [
+                        { "name": "Locals", "variablesReference": local, "expensive": false },
+                        { "name": "Upvalues", "variablesReference": upvalue, "expensive": false },
+                    ]}),
+                );
+            }
+            "variables" if stop => {
+                let index = argument["variablesReference"].as_u64().unwrap_or_default() as usize;
+                let list = Self::get_variable(lua, scope, index)?;
+
+                self.respond(request, json!({ "variables": list }));
+            }
+            "evaluate" if stop => {
+                let frame = argument["frameId"].as_u64().unwrap_or_default() as usize;
+                let code = argument["expression"].as_str().unwrap_or_default();
+                let hook: mlua::Table = lua.named_registry_value(Self::HOOK_NAME)?;
+                let mut result = hook
+                    .get::<mlua::Function>("evaluate")?
+                    .call::<mlua::MultiValue>((frame + Self::LEVEL_OFFSET, code))?;
+
+                if let Some(mlua::Value::Boolean(true)) = result.pop_front() {
+                    let text: Vec<String> = result.iter().map(Self::get_text).collect();
+
+                    self.respond(
+                        request,
+                        json!({ "result": text.join(", "), "variablesReference": 0 }),
+                    );
+                } else {
+                    let text = result.front().map(Self::get_text).unwrap_or_default();
+
+                    self.respond_error(request, &text);
+                }
+            }
+            command => {
+                let message = if !stop && Self::STOP_COMMAND.contains(&command) {
+                    format!("Request \"{command}\" is only supported while stopped.")
+                } else {
+                    format!("Request \"{command}\" is not supported.")
+                };
+
+                self.respond_error(request, &message);
+            }
+        }
+
+        Ok(false)
+    }
+
+    //================================================================
+
+    fn get_frame(lua: &mlua::Lua) -> Vec<Json> {
+        let mut list = Vec::new();
+        let mut level = 0;
+
+        while let Some(frame) = lua.inspect_stack(level, |debug| {
+            let source = debug.source();
+
+            if source.what == "C" {
+                return None;
+            }
+
+            let name = Self::get_source_name(&source.source.unwrap_or_default());
+            let path = std::env::current_dir()
+                .map(|path| path.join(&name).display().to_string())
+                .unwrap_or(name.clone());
+
+            Some(json!({
+                "id": level,
+                "name": debug.names().name.map(|name| name.to_string()).unwrap_or("?".to_string()),
+                "line": debug.current_line().unwrap_or_default(),
+                "column": 1,
+                "source": { "name": name, "path": path },
+            }))
+        }) {
+            if let Some(frame) = frame {
+                list.push(frame);
+            }
+
+            level += 1;
+        }
+
+        list
+    }
+
+    fn get_variable(
+        lua: &mlua::Lua,
+        scope: &mut Vec<DebuggerScope>,
+        index: usize,
+    ) -> mlua::Result<Vec<Json>> {
+        let hook: mlua::Table = lua.named_registry_value(Self::HOOK_NAME)?;
+
+        let pair: Vec<(mlua::Value, mlua::Value)> = match scope.get(index.wrapping_sub(1)) {
+            Some(DebuggerScope::Local(frame)) => hook
+                .get::<mlua::Function>("get_local")?
+                .call::<Vec<mlua::Table>>(frame + Self::LEVEL_OFFSET)?
+                .iter()
+                .map(|pair| Ok((pair.get(1)?, pair.get(2)?)))
+                .collect::<mlua::Result<_>>()?,
+            Some(DebuggerScope::Upvalue(frame)) => hook
+                .get::<mlua::Function>("get_upvalue")?
+                .call::<Vec<mlua::Table>>(frame + Self::LEVEL_OFFSET)?
+                .iter()
+                .map(|pair| Ok((pair.get(1)?, pair.get(2)?)))
+                .collect::<mlua::Result<_>>()?,
+            Some(DebuggerScope::Table(table)) => table
+                .pairs::<mlua::Value, mlua::Value>()
+                .collect::<mlua::Result<_>>()?,
+            None => Vec::new(),
+        };
+
+        let mut list = Vec::new();
+
+        for (name, value) in pair {
+            // Tables can be expanded by the client.
+            let reference = if let mlua::Value::Table(table) = &value {
+                scope.push(DebuggerScope::Table(table.clone()));
+                scope.len()
+            } else {
+                0
+            };
+
+            list.push(json!({
+                "name": name.to_string().unwrap_or_else(|_| format!("{name:?}")),
+                "value": Self::get_text(&value),
+                "type": value.type_name(),
+                "variablesReference": reference,
+            }));
+        }
+
+        Ok(list)
+    }
+
+    fn get_text(value: &mlua::Value) -> String {
+        match value {
+            mlua::Value::String(text) => format!("{:?}", text.to_string_lossy()),
+            _ => value.to_string().unwrap_or_else(|_| format!("{value:?}")),
+        }
+    }
+
+    fn get_depth(lua: &mlua::Lua) -> usize {
+        let mut depth = 0;
+
+        while lua.inspect_stack(depth, |_| ()).is_some() {
+            depth += 1;
+        }
+
+        depth
+    }
+
+    // Convert a chunk name, such as "@./main/main.lua", to a path relative to the working directory.
+    fn get_source_name(source: &str) -> String {
+        let source = source
+            .strip_prefix("@")
+            .unwrap_or(source)
+            .replace("\\", "/");
+
+        source.strip_prefix("./").unwrap_or(&source).to_string()
+    }
+
+    // Convert an absolute path from the client to a path relative to the working directory.
+    fn get_path_name(path: &str) -> String {
+        let path = std::path::Path::new(path);
+
+        if let Ok(work) = std::env::current_dir()
+            && let Ok(path) = path.strip_prefix(work)
+        {
+            return path.display().to_string().replace("\\", "/");
+        }
+
+        path.display().to_string().replace("\\", "/")
+    }
+
+    //================================================================
+
+    fn respond(&self, request: &Json, body: Json) {
+        self.send(json!({
+            "type": "response",
+            "request_seq": request["seq"],
+            "command": request["command"],
+            "success": true,
+            "body": body,
+        }));
+    }
+
+    fn respond_error(&self, request: &Json, message: &str) {
+        self.send(json!({
+            "type": "response",
+            "request_seq": request["seq"],
+            "command": request["command"],
+            "success": false,
+            "message": message,
+        }));
+    }
+
+    fn event(&self, name: &str, body: Json) {
+        self.send(json!({
+            "type": "event",
+            "event": name,
+            "body": body,
+        }));
+    }
+
+    fn send(&self, mut message: Json) {
+        message["seq"] = json!(self.sequence.fetch_add(1, Ordering::Relaxed));
+
+        let message = message.to_string();
+
+        if let Some(stream) = self.stream.lock().unwrap().as_mut() {
+            let _ = write!(stream, "Content-Length: {}\r\n\r\n{message}", message.len());
+        }
+    }
+
+    fn get_state(&self) -> std::sync::MutexGuard<'_, DebuggerState> {
+        self.state.lock().unwrap()
+    }
+
+    fn get_receive(&self) -> std::sync::MutexGuard<'_, Receiver<Json>> {
+        self.receive.lock().unwrap()
+    }
+}
+
+//================================================================
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::time::Duration;
+
+    fn get_frame(message: &Json) -> String {
+        let message = message.to_string();
+
+        format!("Content-Length: {}\r\n\r\n{message}", message.len())
+    }
+
+    // A debugger with a connected client, and the receiver of every message sent to the client.
+    fn get_debugger() -> (Debugger, Receiver<Json>) {
+        let listener = TcpListener::bind(("127.0.0.1", 0)).expect("Error binding listener.");
+        let client = TcpStream::connect(listener.local_addr().expect("Error getting address."))
+            .expect("Error connecting client.");
+        let (server, _) = listener.accept().expect("Error accepting client.");
+
+        let (send, receive) = std::sync::mpsc::channel();
+        std::thread::spawn(move || Debugger::read(client, &send));
+
+        let (_, request) = std::sync::mpsc::channel();
+        let debugger = Debugger::from_receive(request, Arc::new(Mutex::new(Some(server))));
+
+        (debugger, receive)
+    }
+
+    fn get_message(receive: &Receiver<Json>) -> Json {
+        receive
+            .recv_timeout(Duration::from_secs(5))
+            .expect("No message sent.")
+    }
+
+    #[test]
+    fn read() {
+        let first = json!({ "seq": 1, "type": "request", "command": "initialize" });
+        let second = json!({ "seq": 2, "type": "request", "command": "threads" });
+        // Any other header is ignored, and a message with no length is skipped.
+        let data = format!(
+            "Content-Type: application/json\r\n{}\r\n\r\n{}",
+            get_frame(&first),
+            get_frame(&second)
+        );
+
+        let (send, receive) = std::sync::mpsc::channel();
+        Debugger::read(std::io::Cursor::new(data), &send);
+
+        let list: Vec<Json> = receive.try_iter().collect();
+
+        assert_eq!(list.len(), 3);
+        assert_eq!(list[0], first);
+        assert_eq!(list[1], second);
+        // The game is resumed once the client is gone.
+        assert_eq!(list[2]["command"], "disconnect");
+    }
+
+    #[test]
+    fn send() {
+        let (debugger, receive) = get_debugger();
+
+        debugger.event("initialized", json!({}));
+        debugger.event("stopped", json!({ "reason": "pause" }));
+
+        let first = get_message(&receive);
+        let second = get_message(&receive);
+
+        assert_eq!(first["event"], "initialized");
+        assert_eq!(second["body"]["reason"], "pause");
+        // Every message has its own sequence number.
+        assert_eq!(first["seq"], 1);
+        assert_eq!(second["seq"], 2);
+    }
+
+    #[test]
+    fn handle() {
+        let lua = mlua::Lua::new();
+        let (debugger, receive) = get_debugger();
+
+        let request = json!({ "seq": 1, "type": "request", "command": "initialize" });
+        assert!(!debugger.handle(&lua, &request, None).unwrap());
+
+        let response = get_message(&receive);
+        assert_eq!(response["request_seq"], 1);
+        assert_eq!(response["success"], true);
+        assert_eq!(response["body"]["supportsConfigurationDoneRequest"], true);
+        assert_eq!(get_message(&receive)["event"], "initialized");
+
+        let request = json!({
+            "seq": 2,
+            "type": "request",
+            "command": "setBreakpoints",
+            "arguments": {
+                "source": { "path": "main/main.lua" },
+                "breakpoints": [{ "line": 4 }, { "line": 8 }],
+            },
+        });
+        debugger.handle(&lua, &request, None).unwrap();
+
+        assert_eq!(
+            get_message(&receive)["body"]["breakpoints"]
+                .as_array()
+                .map(Vec::len),
+            Some(2)
+        );
+        assert_eq!(
+            debugger.get_state().breakpoint.get("main/main.lua"),
+            Some(&HashSet::from([4, 8]))
+        );
+
+        let request = json!({ "seq": 3, "type": "request", "command": "configurationDone" });
+        debugger.handle(&lua, &request, None).unwrap();
+
+        assert_eq!(get_message(&receive)["success"], true);
+        assert!(debugger.get_state().ready);
+
+        // A disconnect resumes the game, and clears every break-point.
+        let request = json!({ "seq": 4, "type": "request", "command": "disconnect" });
+        assert!(debugger.handle(&lua, &request, None).unwrap());
+
+        get_message(&receive);
+        assert!(debugger.get_state().breakpoint.is_empty());
+    }
+
+    #[test]
+    fn handle_error() {
+        let lua = mlua::Lua::new();
+        let (debugger, receive) = get_debugger();
+
+        let request = json!({ "seq": 1, "type": "request", "command": "stackTrace" });
+        debugger.handle(&lua, &request, None).unwrap();
+
+        let response = get_message(&receive);
+        assert_eq!(response["success"], false);
+        assert_eq!(
+            response["message"],
+            "Request \"stackTrace\" is only supported while stopped."
+        );
+
+        // A request that is never supported is named as such, even while stopped.
+        let request = json!({ "seq": 2, "type": "request", "command": "restartFrame" });
+        debugger
+            .handle(&lua, &request, Some(&mut Vec::new()))
+            .unwrap();
+
+        let response = get_message(&receive);
+        assert_eq!(response["success"], false);
+        assert_eq!(
+            response["message"],
+            "Request \"restartFrame\" is not supported."
+        );
+    }
+}
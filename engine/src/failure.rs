@@ -0,0 +1,238 @@
+use std::cell::RefCell;
+use std::fmt::Display;
+use std::rc::Rc;
+
+//================================================================
+
+pub struct Failure {
+    // Error message.
+    pub message: String,
+    // Lua stack at the point of failure, from the inner-most call.
+    pub frame: Vec<FailureFrame>,
+    // Rust-side error chain, from the outer-most error.
+    pub chain: Vec<String>,
+}
+
+pub struct FailureFrame {
+    pub file: String,
+    pub line: Option<usize>,
+    pub name: Option<String>,
+}
+
+impl Failure {
+    // Registry key of the original "xpcall", so that a script replacing the global can not break error handling.
+    const XPCALL_KEY: &str = "failure_xpcall";
+
+    // Keep the original "xpcall". Must be called on state creation, before any script is run.
+    pub fn set_state(lua: &mlua::Lua) -> mlua::Result<()> {
+        let xpcall: mlua::Function = lua.named_registry_value(Self::XPCALL_KEY)?;
+
+        lua.set_named_registry_value(Self::XPCALL_KEY, xpcall)
+    }
+
+    // Call a Lua function, keeping the Lua stack on failure.
+    pub fn call<A: mlua::IntoLuaMulti, R: mlua::FromLuaMulti>(
+        lua: &mlua::Lua,
+        call: &mlua::Function,
+        argument: A,
+    ) -> Result<R, Self> {
+        let frame = Rc::new(RefCell::new(Vec::new()));
+        let frame_handler = frame.clone();
+
+        // The message handler runs before the stack is unwound, which is the only point where it can be inspected.
+        let handler = lua.create_function(move |lua, error: mlua::Value| {
+            *frame_handler.borrow_mut() = Self::get_frame(lua);
+
+            Ok(error)
+        })?;
+        let xpcall: mlua::Function = lua.named_registry_value(Self::XPCALL_KEY)?;
+
+        let mut value = xpcall.call::<mlua::MultiValue>((call, handler, argument))?;
+
+        if let Some(mlua::Value::Boolean(true)) = value.pop_front() {
+            return Ok(R::from_lua_multi(value, lua)?);
+        }
+
+        let mut failure = match value.pop_front() {
+            // Rust-side error, raised from a call-back.
+            Some(mlua::Value::Error(error)) => Self::from(*error),
+            // Lua-side error, raised with "error".
+            Some(error) => {
+                let message = error.to_string().unwrap_or_else(|_| format!("{error:?}"));
+
+                Self {
+                    message: message.clone(),
+                    frame: Vec::new(),
+                    chain: vec![message],
+                }
+            }
+            None => Self::from(mlua::Error::runtime("Unknown error.")),
+        };
+        failure.frame = frame.take();
+
+        Err(failure)
+    }
+
+    // Create a table for the "fail" call-back. The table can be converted to a string with "tostring" or "..", but is
+    // not a string itself.
+    pub fn get_table(&self, lua: &mlua::Lua) -> mlua::Result<mlua::Table> {
+        let table = lua.create_table()?;
+        let frame = lua.create_table()?;
+
+        for entry in &self.frame {
+            let value = lua.create_table()?;
+            value.set("file", entry.file.as_str())?;
+            value.set("line", entry.line)?;
+            value.set("name", entry.name.as_deref())?;
+            frame.push(value)?;
+        }
+
+        table.set("message", self.message.as_str())?;
+        table.set("frame", frame)?;
+        table.set("chain", self.chain.clone())?;
+
+        let text = self.to_string();
+        let meta = lua.create_table()?;
+        meta.set(
+            "__tostring",
+            lua.create_function(move |_, _: mlua::Value| Ok(text.clone()))?,
+        )?;
+        meta.set(
+            "__concat",
+            lua.create_function(|_, (a, b): (mlua::Value, mlua::Value)| {
+                Ok(format!("{}{}", a.to_string()?, b.to_string()?))
+            })?,
+        )?;
+        table.set_metatable(Some(meta))?;
+
+        Ok(table)
+    }
+
+    // Write a crash report to the working directory.
+    pub fn write(&self) {
+        let time = chrono::Local::now();
+        // Milli-second precision, so that two failures within the same second will not over-write each other.
+        let file = format!("crash_{}.txt", time.format("%d_%m_%y_%H_%M_%S_%3f"));
+
+        if let Err(error) = std::fs::write(&file, self.to_string()) {
+            eprintln!("Error writing crash report \"{file}\": {error}");
+        }
+    }
+
+    fn get_frame(lua: &mlua::Lua) -> Vec<FailureFrame> {
+        let mut list = Vec::new();
+        let mut level = 0;
+
+        while let Some(frame) = lua.inspect_stack(level, |debug| {
+            let source = debug.source();
+
+            // Skip native functions, such as the message handler itself.
+            if source.what == "C" {
+                return None;
+            }
+
+            Some(FailureFrame {
+                file: source.short_src.unwrap_or_default().to_string(),
+                line: debug.current_line(),
+                name: debug.names().name.map(|name| name.to_string()),
+            })
+        }) {
+            if let Some(frame) = frame {
+                list.push(frame);
+            }
+
+            level += 1;
+        }
+
+        list
+    }
+
+    fn get_chain(error: &(dyn std::error::Error + 'static)) -> Vec<String> {
+        let mut list: Vec<String> = Vec::new();
+        let mut error = Some(error);
+
+        while let Some(value) = error {
+            // Call-back errors carry a text trace-back in their message, only keep the cause.
+            match value.downcast_ref::<mlua::Error>() {
+                Some(mlua::Error::CallbackError { cause, .. }) => {
+                    error = Some(cause.as_ref());
+                    continue;
+                }
+                Some(mlua::Error::WithContext { context, cause }) => {
+                    list.push(context.clone());
+                    error = Some(cause.as_ref());
+                    continue;
+                }
+                _ => {}
+            }
+
+            let text = value.to_string();
+
+            // Wrapped errors will usually repeat the message of their source.
+            if list.last() != Some(&text) {
+                list.push(text);
+            }
+
+            error = value.source();
+        }
+
+        list
+    }
+}
+
+impl From<mlua::Error> for Failure {
+    fn from(error: mlua::Error) -> Self {
+        let chain = Self::get_chain(&error);
+
+        Self {
+            message: chain.first().cloned().unwrap_or_default(),
+            frame: Vec::new(),
+            chain,
+        }
+    }
+}
+
+impl From<anyhow::Error> for Failure {
+    fn from(error: anyhow::Error) -> Self {
+        let chain = Self::get_chain(error.as_ref());
+
+        Self {
+            message: chain.first().cloned().unwrap_or_default(),
+            frame: Vec::new(),
+            chain,
+        }
+    }
+}
+
+impl Display for Failure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{}", self.message)?;
+
+        if !self.frame.is_empty() {
+            writeln!(f, "\nStack trace-back:")?;
+
+            for frame in &self.frame {
+                write!(f, "    {}", frame.file)?;
+
+                if let Some(line) = frame.line {
+                    write!(f, ":{line}")?;
+                }
+                if let Some(name) = &frame.name {
+                    write!(f, " in function \"{name}\"")?;
+                }
+
+                writeln!(f)?;
+            }
+        }
+
+        if self.chain.len() > 1 {
+            writeln!(f, "\nError chain:")?;
+
+            for entry in &self.chain {
+                writeln!(f, "    {entry}")?;
+            }
+        }
+
+        Ok(())
+    }
+}
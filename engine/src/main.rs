@@ -1,12 +1,34 @@
+mod argument;
+mod console;
+mod debugger;
+mod failure;
+mod meta;
 mod module;
+mod pack;
+mod reload;
+mod signature;
 
 //================================================================
 
+use crate::argument::*;
+use crate::console::*;
+use crate::debugger::*;
+use crate::failure::*;
+use crate::meta::*;
+use crate::module::archive::*;
+use crate::module::data::*;
+use crate::module::profile::*;
+use crate::module::task::*;
+use crate::module::vfs::*;
+use crate::pack::*;
+use crate::reload::*;
+use crate::signature::*;
 use mimalloc::MiMalloc;
 use mlua::prelude::*;
 use raylib::prelude::*;
 use serde::Deserialize;
-use std::io::Read;
+use std::time::Duration;
+use std::time::Instant;
 
 #[global_allocator]
 static GLOBAL: MiMalloc = MiMalloc;
@@ -29,12 +51,31 @@ struct ContextInfo {
     full: bool,
     rate: u32,
     log: bool,
+    reload: Option<bool>,
+    // Only read to refuse it, as safe mode is set in the static info file instead.
+    safe: Option<serde_json::Value>,
+    window: Option<bool>,
+    console: Option<bool>,
+    profile: Option<bool>,
+    step: Option<u32>,
+    key: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ContextSafe {
+    root: String,
+}
+
+// Every setting that must be known before any game code is run, from the "main/info.json" file.
+#[derive(Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ContextFile {
+    safe: Option<ContextSafe>,
 }
 
 impl Context {
     fn new(script: &Script) -> anyhow::Result<Self> {
-        let info = script.info.call::<mlua::Value>(())?;
-        let info: ContextInfo = script.lua.from_value(info)?;
+        let info = script.get_info()?;
 
         let mut flag = 0;
 
@@ -79,11 +120,115 @@ impl Context {
     }
 }
 
+// Fixed-rate loop, used in place of the window's frame rate when running without a window.
+struct Headless {
+    rate: Duration,
+    time: Instant,
+}
+
+impl Headless {
+    fn new(rate: u32) -> Self {
+        Self {
+            rate: if rate > 0 {
+                Duration::from_secs_f64(1.0 / rate as f64)
+            } else {
+                Duration::ZERO
+            },
+            time: Instant::now(),
+        }
+    }
+
+    // Wait until the next tick, and return the time (in seconds) since the last tick.
+    fn wait(&mut self) -> f32 {
+        let time = self.time.elapsed();
+
+        if time < self.rate {
+            std::thread::sleep(self.rate - time);
+        }
+
+        let delta = self.time.elapsed().as_secs_f32();
+        self.time = Instant::now();
+
+        delta
+    }
+}
+
+// Fixed time-step loop, calling "update" at a fixed rate, and "draw" once per frame.
+struct Step {
+    // Time (in seconds) of every update.
+    step: f32,
+    // Time (in seconds) yet to be updated.
+    time: f32,
+}
+
+impl Step {
+    const ENTRY_UPDATE: &str = "update";
+    const ENTRY_DRAW: &str = "draw";
+    // Longest frame time (in seconds) to catch up on, and most updates in a single frame.
+    // Past either limit, time is dropped rather than letting every later frame fall further behind.
+    const TIME_LIMIT: f32 = 0.25;
+    const STEP_LIMIT: usize = 8;
+
+    fn new(rate: u32) -> Self {
+        Self {
+            step: 1.0 / rate.max(1) as f32,
+            time: 0.0,
+        }
+    }
+
+    // Update once, for use with the fixed-rate loop when running without a window.
+    fn tick(&self, script: &Script) -> Result<Option<bool>, Failure> {
+        let update: mlua::Function = script.table.get(Self::ENTRY_UPDATE)?;
+
+        Failure::call(&script.lua, &update, (&script.table, self.step))
+    }
+
+    // Update as many times as needed to catch up with the frame time, then draw.
+    fn frame(&mut self, script: &Script) -> Result<Option<bool>, Failure> {
+        if unsafe { ffi::WindowShouldClose() } {
+            return Ok(Some(false));
+        }
+
+        let update: mlua::Function = script.table.get(Self::ENTRY_UPDATE)?;
+        let draw: mlua::Function = script.table.get(Self::ENTRY_DRAW)?;
+
+        self.time += unsafe { ffi::GetFrameTime() }.min(Self::TIME_LIMIT);
+
+        let mut count = 0;
+
+        while self.time >= self.step {
+            if count == Self::STEP_LIMIT {
+                self.time %= self.step;
+                break;
+            }
+
+            let code: Option<bool> =
+                Failure::call(&script.lua, &update, (&script.table, self.step))?;
+
+            if code.is_some() {
+                return Ok(code);
+            }
+
+            self.time -= self.step;
+            count += 1;
+        }
+
+        // Fraction of an update left over, to interpolate in between the last two updates.
+        let alpha = self.time / self.step;
+
+        crate::module::screen::draw_frame(&script.lua, || {
+            Failure::call(&script.lua, &draw, (&script.table, alpha))
+        })?;
+
+        Ok(None)
+    }
+}
+
 //================================================================
 
 enum ScriptState {
     Success,
-    Failure(String),
+    Failure(Failure),
 }
 
 struct Script {
@@ -91,71 +236,334 @@ struct Script {
     state: ScriptState,
     table: mlua::Table,
     info: mlua::Function,
-    main: mlua::Function,
+    // Only optional with the engine-driven loop, which calls "update" and "draw" instead.
+    main: Option<mlua::Function>,
     fail: mlua::Function,
+    argument: Argument,
 }
 
 impl Script {
-    const MAIN_PATH: &str = "main/main";
-    const MAIN_FILE: &str = "main";
+    const MAIN_FOLDER: &str = "main";
+    const INFO_FILE: &str = "info.json";
     const ENTRY_INFO: &str = "info";
     const ENTRY_MAIN: &str = "main";
     const ENTRY_FAIL: &str = "fail";
     const HOOK_NAME: &str = "flak";
+    const SAFE_CODE: &str = r#"
+-- Remove native module loading, and module loading from disk. Every module is loaded through the virtual file
+-- system instead, in text mode only.
+package.loadlib = nil
+package.path = ""
+package.cpath = ""
+package.loaders[4] = nil
+package.loaders[3] = nil
+package.loaders[2] = nil
+
+-- Only allow text chunks, as malformed byte-code can break out of the VM.
+local load = load
+
+function _G.load(chunk, name, _, environment)
+    return load(chunk, name, "t", environment)
+end
+
+function _G.loadstring(chunk, name)
+    return load(chunk, name, "t")
+end
+
+loadfile = nil
+dofile = nil
+string.dump = nil
+"#;
+
+    fn new(argument: &Argument, set_window_global: bool) -> anyhow::Result<Self> {
+        // Safe mode must be known before any game code is run, so it is read from a static file rather than from
+        // "info". Only one state is ever created, and no game code is run before it is.
+        let mut vfs = Vfs::default();
+        let archive = Self::set_mount(&mut vfs, argument)?;
+
+        let file = Self::get_file(&mut vfs)?;
+
+        let lua = if let Some(safe) = &file.safe {
+            if argument.debug.is_some() {
+                return Err(anyhow::anyhow!(
+                    "The debugger requires the Lua debug library, which is not available in safe mode."
+                ));
+            }
+
+            // Malformed byte-code can break out of the VM, so byte-code is refused before any of it is run.
+            if Self::is_code(&mut vfs, argument)? {
+                return Err(anyhow::anyhow!(
+                    "Safe mode can not be used with byte-code. Use \"engine pack --source\" to pack every Lua file as source."
+                ));
+            }
+
+            // With no "main" ZIP archive, the "main" folder is mounted instead, as no module is loaded from disk.
+            if !archive {
+                Self::check_main(argument, "can not be loaded in safe mode")?;
+                vfs.mount_folder(Self::MAIN_FOLDER, Self::MAIN_FOLDER)?;
+            }
+
+            vfs.set_code(false);
+
+            let lua = Self::new_safe()?;
+            lua.set_app_data(DataRoot::new(&safe.root)?);
+
+            lua
+        } else {
+            let lua = unsafe { Lua::unsafe_new() };
+            crate::module::texture::set_batch(&lua)?;
+
+            // Attach before any game code is run, so that every break-point can be hit.
+            if let Some(port) = argument.debug {
+                Debugger::attach(&lua, port)?;
+            }
+
+            lua
+        };
+
+        Failure::set_state(&lua)?;
+
+        lua.set_app_data(vfs);
+        Vfs::set_loader(&lua)?;
+
+        // With a signature required, every module must come from the signed archive (or a mount), never from disk.
+        if Signature::is_required() {
+            Self::set_loader_list(&lua)?;
+        }
+
+        let script = Self::new_entry(lua, argument)?;
+
+        script.set_state(set_window_global)?;
+
+        Ok(script)
+    }
 
-    fn new(set_window_global: bool) -> anyhow::Result<Self> {
-        let lua = unsafe { Lua::unsafe_new() };
+    fn new_safe() -> anyhow::Result<Lua> {
+        let library = mlua::StdLib::TABLE
+            | mlua::StdLib::STRING
+            | mlua::StdLib::MATH
+            | mlua::StdLib::BIT
+            | mlua::StdLib::PACKAGE;
 
-        let main = std::path::Path::new(Self::MAIN_FILE);
+        // FFI is not loaded, as FFI data is not bounds-checked. The texture batch buffer is a user-data instead.
+        let lua = unsafe { Lua::unsafe_new_with(library, mlua::LuaOptions::default()) };
 
-        if main.is_file() {
-            let global = lua.globals();
-            let loader = global.get::<mlua::Table>("package")?;
-            let loader = loader.get::<mlua::Table>("loaders")?;
+        lua.load(Self::SAFE_CODE).set_name("=safe").exec()?;
 
-            let file = std::fs::File::open(Self::MAIN_FILE)?;
-            let mut file = zip::ZipArchive::new(file)?;
+        Ok(lua)
+    }
 
-            loader.push(lua.create_function_mut(move |lua, path: String| {
-                let token: Vec<&str> = path.split(&format!("{}/", Self::MAIN_FILE)).collect();
+    // Mount the "main" ZIP archive, if any, for "require" and every asset. Returns true if an archive was mounted.
+    fn set_mount(vfs: &mut Vfs, argument: &Argument) -> anyhow::Result<bool> {
+        // The "main" ZIP archive is mounted in place of the "main" folder, for both require and every asset.
+        let archive = if std::path::Path::new(&argument.file).is_file() {
+            vfs.mount_main(&argument.file, Self::MAIN_FOLDER)?;
+            true
+        } else if let Some(path) = Pack::get_embed() {
+            // The "main" ZIP archive may be embedded into the engine executable itself, by "engine pack --embed".
+            vfs.mount_main(&path, Self::MAIN_FOLDER)?;
+            true
+        } else if Signature::is_required() {
+            return Err(anyhow::anyhow!(
+                "A signed \"{}\" ZIP archive is required.",
+                argument.file
+            ));
+        } else {
+            false
+        };
 
-                if let Some(path) = token.get(1)
-                    && let Ok(mut entry) = file.by_name(&format!("{path}.lua"))
-                {
-                    let mut buffer = String::new();
-                    entry.read_to_string(&mut buffer)?;
-                    return Ok(lua.load(buffer).into_function());
+        // An entry module outside of the "main" folder can never be in the archive.
+        if archive {
+            Self::check_main(argument, "can not be loaded from the ZIP archive")?;
+        }
+
+        if Signature::is_required()
+            && vfs
+                .find_file(&Self::get_entry_path(argument), ArchiveKey::BUILD_KEY)?
+                .is_none()
+        {
+            return Err(anyhow::anyhow!(
+                "Entry module \"{}\" is not in the signed \"{}\" ZIP archive.",
+                argument.main,
+                argument.file
+            ));
+        }
+
+        Ok(archive)
+    }
+
+    // Check that the entry module is in the "main" folder.
+    fn check_main(argument: &Argument, reason: &str) -> anyhow::Result<()> {
+        if argument
+            .main
+            .replace(".", "/")
+            .starts_with(&format!("{}/", Self::MAIN_FOLDER))
+        {
+            Ok(())
+        } else {
+            Err(anyhow::anyhow!(
+                "Entry module \"{}\" is outside of the \"{}\" folder, and {reason}.",
+                argument.main,
+                Self::MAIN_FOLDER
+            ))
+        }
+    }
+
+    // Read the static info file, from the "main" ZIP archive or from the "main" folder. A missing file is empty.
+    fn get_file(vfs: &mut Vfs) -> anyhow::Result<ContextFile> {
+        let path = format!("{}/{}", Self::MAIN_FOLDER, Self::INFO_FILE);
+
+        let data = match vfs.find_file(&path, ArchiveKey::BUILD_KEY)? {
+            Some((file, _)) => file.get_data()?,
+            None if Signature::is_required() => return Ok(ContextFile::default()),
+            None => match std::fs::read(&path) {
+                Ok(data) => data,
+                Err(error) if error.kind() == std::io::ErrorKind::NotFound => {
+                    return Ok(ContextFile::default());
                 }
+                Err(error) => return Err(error.into()),
+            },
+        };
+
+        serde_json::from_slice(&data)
+            .map_err(|error| anyhow::anyhow!("Error reading \"{path}\": {error}"))
+    }
+
+    // Only keep the "package.preload" loader and the virtual file system loader, so that no module is ever loaded
+    // from disk by "package.path" or "package.cpath".
+    fn set_loader_list(lua: &Lua) -> anyhow::Result<()> {
+        let package: mlua::Table = lua.globals().get("package")?;
+        package.set("path", "")?;
+        package.set("cpath", "")?;
 
-                Err(mlua::Error::external(format!(
-                    "No module \"{path}\" found in the \"main\" ZIP archive."
-                )))
-            })?)?;
+        let loader: mlua::Table = package.get("loaders")?;
+
+        for i in (3..=loader.raw_len()).rev() {
+            loader.raw_set(i, mlua::Nil)?;
         }
 
-        let table: mlua::Table = lua
-            .load(format!("require(\"{}\")", Self::MAIN_PATH))
-            .eval()?;
+        Ok(())
+    }
+
+    fn get_entry_path(argument: &Argument) -> String {
+        format!("{}.lua", argument.main.replace(".", "/"))
+    }
+
+    // Check if the entry module is compiled to byte-code, which starts with the escape character (as Lua checks).
+    fn is_code(vfs: &mut Vfs, argument: &Argument) -> anyhow::Result<bool> {
+        let path = Self::get_entry_path(argument);
+
+        let data = match vfs.find_file(&path, ArchiveKey::BUILD_KEY)? {
+            Some((file, _)) => file.get_data()?,
+            None => std::fs::read(&path).unwrap_or_default(),
+        };
+
+        Ok(data.first() == Some(&0x1B))
+    }
+
+    // Load the entry module, and take every entry function from it.
+    fn new_entry(lua: Lua, argument: &Argument) -> anyhow::Result<Self> {
+        let table: mlua::Table = lua.load(format!("require(\"{}\")", argument.main)).eval()?;
         let info = table.get(Self::ENTRY_INFO)?;
         let main = table.get(Self::ENTRY_MAIN)?;
         let fail = table.get(Self::ENTRY_FAIL)?;
 
-        let script = Self {
+        Ok(Self {
             lua,
             state: ScriptState::Success,
             table,
             info,
             main,
             fail,
-        };
+            argument: argument.clone(),
+        })
+    }
+
+    // Set every global and every engine feature requested in "info", once the state is known to be final.
+    fn set_state(&self, set_window_global: bool) -> anyhow::Result<()> {
+        let info = self.get_info()?;
+
+        // Safe mode set here would only be known once the entry module has been run with full access.
+        if info.safe.is_some() {
+            return Err(anyhow::anyhow!(
+                "Safe mode must be set in the \"{}/{}\" file, rather than in \"{}\".",
+                Self::MAIN_FOLDER,
+                Self::INFO_FILE,
+                Self::ENTRY_INFO
+            ));
+        }
+
+        if info.step.is_none() {
+            self.get_main()?;
+        }
+
+        // Every module loaded after the entry module can be encrypted with a key from "info".
+        if let Some(key) = info.key {
+            self.lua.set_app_data(ArchiveKey::new(key));
+        }
+
+        // Must be set before any global is set, so that every engine function can be wrapped.
+        if info.profile.unwrap_or(false) {
+            self.lua.set_app_data(Profile::new());
+        }
 
-        script.set_global(false)?;
+        self.set_global(false)?;
 
         if set_window_global {
-            script.set_global(true)?;
+            self.set_global(true)?;
         }
 
-        Ok(script)
+        if info.reload.unwrap_or(false) {
+            self.lua.set_app_data(Reload::new(
+                &self.argument.main,
+                Self::MAIN_FOLDER,
+                &self.argument.file,
+            ));
+        }
+
+        if info.console.unwrap_or(false) {
+            self.lua.set_app_data(Console::new());
+        }
+
+        Ok(())
+    }
+
+    // Take every entry function from the script table again, after the table has been patched by a hot-reload.
+    fn set_entry(&mut self) -> mlua::Result<()> {
+        self.main = self.table.get(Self::ENTRY_MAIN)?;
+        self.fail = self.table.get(Self::ENTRY_FAIL)?;
+
+        Ok(())
+    }
+
+    fn get_main(&self) -> mlua::Result<&mlua::Function> {
+        self.main.as_ref().ok_or_else(|| {
+            mlua::Error::runtime(format!(
+                "The script table has no \"{}\" function.",
+                Self::ENTRY_MAIN
+            ))
+        })
+    }
+
+    fn get_info(&self) -> anyhow::Result<ContextInfo> {
+        let info = self.info.call::<mlua::Value>(())?;
+        let mut info: ContextInfo = self.lua.from_value(info)?;
+
+        // Command-line arguments take priority over the script.
+        if self.argument.log {
+            info.log = true;
+        }
+        if self.argument.windowed {
+            info.full = false;
+        }
+        if let Some(scale) = self.argument.scale {
+            info.scale = scale;
+        }
+        if self.argument.headless {
+            info.window = Some(false);
+        }
+
+        Ok(info)
     }
 
     fn set_global(&self, window: bool) -> anyhow::Result<()> {
@@ -169,24 +577,21 @@ impl Script {
             table
         };
 
-        if window {
-            crate::module::window::set_global(&self.lua, &global)?;
-            crate::module::screen::set_global(&self.lua, &global)?;
-            crate::module::texture::set_global(&self.lua, &global)?;
-            crate::module::font::set_global(&self.lua, &global)?;
-            crate::module::sound::set_global(&self.lua, &global)?;
-            crate::module::music::set_global(&self.lua, &global)?;
-            crate::module::input::set_global(&self.lua, &global)?;
-        } else {
-            crate::module::data::set_global(&self.lua, &global)?;
-            crate::module::archive::set_global(&self.lua, &global)?;
-            crate::module::network::set_global(&self.lua, &global)?;
+        crate::module::set_global(&self.lua, &global, window)?;
+
+        if !window {
+            global.set("argument", self.argument.list.clone())?;
 
             self.lua.globals().set(
                 "print",
-                self.lua.create_function(|_, value: mlua::Value| {
+                self.lua.create_function(|lua, value: mlua::Value| {
                     println!("{value:#?}");
 
+                    // Capture the output into the developer console, if any.
+                    if let Some(mut console) = lua.app_data_mut::<Console>() {
+                        console.push(&value.to_string().unwrap_or_else(|_| format!("{value:?}")));
+                    }
+
                     Ok(())
                 })?,
             )?;
@@ -211,66 +616,156 @@ impl Script {
 
 //================================================================
 
-fn throw_error<T, E: std::string::ToString + std::fmt::Debug>(result: Result<T, E>) -> T {
+fn throw_error<T, E: std::string::ToString + std::fmt::Debug>(
+    result: Result<T, E>,
+    window: bool,
+) -> T {
     match result {
         Ok(value) => value,
         Err(error) => {
-            rfd::MessageDialog::new()
-                .set_level(rfd::MessageLevel::Error)
-                .set_title("Fatal Error")
-                .set_description(error.to_string())
-                .show();
+            // Without a window, there might not be a display to show a dialog on.
+            if window {
+                rfd::MessageDialog::new()
+                    .set_level(rfd::MessageLevel::Error)
+                    .set_title("Fatal Error")
+                    .set_description(error.to_string())
+                    .show();
+            }
             panic!("{error:?}")
         }
     }
 }
 
 fn main() -> anyhow::Result<()> {
-    /*
+    // Write the meta files, in place of running a game.
+    if std::env::args().nth(1).as_deref() == Some(Meta::COMMAND) {
+        return Meta::run(std::env::args().skip(2));
+    }
+
+    // Pack the game into a distributable bundle, in place of running a game.
+    if std::env::args().nth(1).as_deref() == Some(Pack::COMMAND) {
+        return Pack::run(std::env::args().skip(2));
+    }
+
     let hook = std::panic::take_hook();
 
     std::panic::set_hook(Box::new(move |panic_info| {
         let time = chrono::Local::now();
-        let file = format!("panic_{}.txt", time.format("%d_%m_%y_%H_%M_%S"));
-        std::fs::write(file, format!("{}", panic_info)).unwrap();
+        let file = format!("panic_{}.txt", time.format("%d_%m_%y_%H_%M_%S_%3f"));
+        let trace = std::backtrace::Backtrace::force_capture();
+
+        if let Err(error) = std::fs::write(&file, format!("{panic_info}\n\n{trace}")) {
+            eprintln!("Error writing panic report \"{file}\": {error}");
+        }
 
         hook(panic_info);
     }));
-    */
 
-    let mut script = throw_error(Script::new(false));
-    let _context = throw_error(Context::new(&script));
+    let Some(argument) = Argument::new()? else {
+        println!("{HELP}");
+        return Ok(());
+    };
+    let mut script = throw_error(Script::new(&argument, false), !argument.headless);
+    let info = throw_error(script.get_info(), !argument.headless);
+    let window = info.window.unwrap_or(true);
+
+    // Only create the window and audio device if requested. Otherwise, drive "main" at a fixed rate.
+    let _context = if window {
+        Some(throw_error(Context::new(&script), window))
+    } else {
+        None
+    };
+    let mut headless = if window {
+        None
+    } else {
+        Some(Headless::new(info.step.unwrap_or(info.rate)))
+    };
+    // Only drive "update" and "draw" if requested. Otherwise, "main" owns the loop.
+    let mut step = info.step.map(Step::new);
+    // Text of the last crash report, so that a script failing over and over (such as on every re-load) will only
+    // write one report for every distinct failure.
+    let mut report = None;
 
     loop {
+        // A hot-reload patches the script table in place, but not the entry functions taken from it. Only reached
+        // once per frame with "info.step" or without a window; a "main" function owning the loop must return first.
+        if Reload::take(&script.lua)
+            && let Err(error) = script.set_entry()
+        {
+            script.state = ScriptState::Failure(Failure::from(error));
+        }
+
         match script.state {
             ScriptState::Success => {
-                let code = script.main.call::<bool>(&script.table);
-
-                if let Err(error) = code {
-                    script.state = ScriptState::Failure(error.to_string());
-                } else if let Ok(code) = code {
-                    if code {
-                        let new = Script::new(true);
+                let code = if let Some(headless) = &mut headless {
+                    let delta = headless.wait();
+
+                    // Tick boundary, for the profiler.
+                    Profile::frame(&script.lua);
+
+                    // Tick boundary, resume every task, and check for any script change.
+                    TaskList::update(&script.lua)
+                        .and_then(|_| Reload::update(&script.lua))
+                        .map_err(Failure::from)
+                        .and_then(|_| {
+                            if let Some(step) = &step {
+                                step.tick(&script)
+                            } else {
+                                Failure::call(
+                                    &script.lua,
+                                    script.get_main()?,
+                                    (&script.table, delta),
+                                )
+                            }
+                        })
+                } else if let Some(step) = &mut step {
+                    step.frame(&script)
+                } else {
+                    script
+                        .get_main()
+                        .map_err(Failure::from)
+                        .and_then(|main| Failure::call::<_, bool>(&script.lua, main, &script.table))
+                        .map(Some)
+                };
+
+                match code {
+                    Err(error) => {
+                        script.state = ScriptState::Failure(error);
+                    }
+                    Ok(Some(true)) => {
+                        let new = Script::new(&argument, window);
 
                         if let Err(error) = new {
-                            script.state = ScriptState::Failure(error.to_string());
+                            script.state = ScriptState::Failure(Failure::from(error));
                         } else if let Ok(new) = new {
                             script = new;
                         }
-                    } else {
-                        break;
                     }
+                    Ok(Some(false)) => break,
+                    // Keep ticking, "main" (or "update") is called again on the next tick.
+                    Ok(None) => {}
                 }
             }
             ScriptState::Failure(ref error) => {
-                let code =
-                    throw_error(script.fail.call::<bool>((&script.table, error.to_string())));
+                let text = error.to_string();
+
+                if report.as_ref() != Some(&text) {
+                    error.write();
+                    report = Some(text);
+                }
+
+                let code = throw_error(
+                    error
+                        .get_table(&script.lua)
+                        .and_then(|error| script.fail.call::<bool>((&script.table, error))),
+                    window,
+                );
 
                 if code {
-                    let new = Script::new(true);
+                    let new = Script::new(&argument, window);
 
                     if let Err(error) = new {
-                        script.state = ScriptState::Failure(error.to_string());
+                        script.state = ScriptState::Failure(Failure::from(error));
                     } else if let Ok(new) = new {
                         script = new;
                     }
@@ -0,0 +1,714 @@
+use crate::meta::document::get_array;
+use crate::meta::document::get_text;
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+//================================================================
+
+const INDENT: &str = "    ";
+// Every Teal meta-method, by operator.
+const TEAL_OPERATOR: [&str; 14] = [
+    "add", "sub", "mul", "div", "mod", "pow", "unm", "concat", "len", "eq", "lt", "le", "call",
+    "tostring",
+];
+// Every TypeScriptToLua operator type, by operator. A unary operator has no parameter.
+const TYPE_SCRIPT_OPERATOR: [(&str, &str, bool); 9] = [
+    ("add", "LuaAdditionMethod", false),
+    ("sub", "LuaSubtractionMethod", false),
+    ("mul", "LuaMultiplicationMethod", false),
+    ("div", "LuaDivisionMethod", false),
+    ("mod", "LuaModuloMethod", false),
+    ("pow", "LuaPowerMethod", false),
+    ("concat", "LuaConcatMethod", false),
+    ("unm", "LuaNegationMethod", true),
+    ("len", "LuaLengthMethod", true),
+];
+
+//================================================================
+
+// A module, and every sub-module within it (such as "input.board" in "input").
+#[derive(Default)]
+struct Module<'a> {
+    info: String,
+    function: Vec<&'a Value>,
+    child: BTreeMap<String, Module<'a>>,
+}
+
+impl<'a> Module<'a> {
+    fn new(description: &'a Value) -> BTreeMap<String, Self> {
+        let mut list: BTreeMap<String, Self> = BTreeMap::new();
+
+        for item in get_array(description, "module") {
+            let name = get_text(item, "name");
+            let mut path = name.split('.');
+            let mut module = list
+                .entry(path.next().unwrap_or_default().to_string())
+                .or_default();
+
+            for name in path {
+                module = module.child.entry(name.to_string()).or_default();
+            }
+
+            module.info = get_text(item, "info");
+            module.function.extend(get_array(item, "function"));
+        }
+
+        list
+    }
+}
+
+//================================================================
+
+// Create the Teal declaration file.
+pub fn get_teal(description: &Value) -> String {
+    let mut buffer = String::from("global record flak\n");
+
+    // Enumerations are integers in Lua, so every enumeration is given as an integer, with a record for its table.
+    // A type and a field in a record can not share a name.
+    let enumeration: Vec<String> = get_array(description, "enumeration")
+        .iter()
+        .map(|item| get_text(item, "name"))
+        .collect();
+
+    for item in get_array(description, "enumeration") {
+        let name = get_text(item, "name");
+        let table = get_text(item, "table");
+
+        push_comment(&mut buffer, "--", item, 1);
+        buffer.push_str(&format!("{INDENT}record {name}Table\n"));
+
+        for variant in get_array(item, "variant") {
+            buffer.push_str(&format!(
+                "{INDENT}{INDENT}{}: integer\n",
+                get_text(variant, "name")
+            ));
+        }
+
+        buffer.push_str(&format!("{INDENT}end\n"));
+        buffer.push_str(&format!("{INDENT}{table}: {name}Table\n\n"));
+    }
+
+    // A record can not be declared twice, so a class with the same name as a record (such as a native Vector2)
+    // is declared along with the record fields.
+    let class: Vec<String> = get_array(description, "class")
+        .iter()
+        .map(|item| get_text(item, "name"))
+        .collect();
+
+    for item in get_array(description, "record") {
+        if class.contains(&get_text(item, "name")) {
+            continue;
+        }
+
+        push_comment(&mut buffer, "--", item, 1);
+        buffer.push_str(&format!("{INDENT}record {}\n", get_text(item, "name")));
+        push_teal_field(&mut buffer, item, &enumeration);
+        buffer.push_str(&format!("{INDENT}end\n\n"));
+    }
+
+    for item in get_array(description, "class") {
+        let name = get_text(item, "name");
+
+        push_comment(&mut buffer, "--", item, 1);
+        buffer.push_str(&format!("{INDENT}record {name}\n"));
+
+        if let Some(record) = get_array(description, "record")
+            .iter()
+            .find(|record| get_text(record, "name") == name)
+        {
+            push_teal_field(&mut buffer, record, &enumeration);
+        }
+
+        for method in get_array(item, "method") {
+            let operator = get_text(method, "operator");
+
+            if operator.is_empty() {
+                push_comment(&mut buffer, "--", method, 2);
+                push_teal_function(&mut buffer, method, Some(&name), &enumeration, 2);
+            } else if TEAL_OPERATOR.contains(&operator.as_str()) {
+                let mut parameter = vec![format!("self: {name}")];
+                parameter.extend(get_teal_parameter(method, &enumeration));
+
+                push_comment(&mut buffer, "--", method, 2);
+                buffer.push_str(&format!(
+                    "{INDENT}{INDENT}metamethod __{operator}: function({}){}\n",
+                    parameter.join(", "),
+                    get_teal_result(method, &enumeration)
+                ));
+            }
+        }
+
+        buffer.push_str(&format!("{INDENT}end\n\n"));
+    }
+
+    for (name, module) in Module::new(description) {
+        push_teal_module(&mut buffer, &name, &module, &enumeration, 1);
+    }
+
+    buffer = format!("{}\nend\n", buffer.trim_end());
+    buffer
+}
+
+fn push_teal_module(
+    buffer: &mut String,
+    name: &str,
+    module: &Module,
+    enumeration: &[String],
+    level: usize,
+) {
+    let indent = INDENT.repeat(level);
+
+    if !module.info.is_empty() {
+        buffer.push_str(&format!("{indent}-- {}\n", module.info));
+    }
+
+    // A nested record only declares a type, the field for the module table is set after it.
+    buffer.push_str(&format!("{indent}record {name}_module\n"));
+
+    for (name, child) in &module.child {
+        push_teal_module(buffer, name, child, enumeration, level + 1);
+    }
+
+    for function in &module.function {
+        push_comment(buffer, "--", function, level + 1);
+        push_teal_function(buffer, function, None, enumeration, level + 1);
+    }
+
+    buffer.push_str(&format!("{indent}end\n"));
+    buffer.push_str(&format!("{indent}{name}: {name}_module\n\n"));
+}
+
+fn push_teal_field(buffer: &mut String, record: &Value, enumeration: &[String]) {
+    for field in get_array(record, "field") {
+        push_comment(buffer, "--", field, 2);
+        buffer.push_str(&format!(
+            "{INDENT}{INDENT}{}: {}\n",
+            get_text(field, "name"),
+            get_teal_kind(&get_text(field, "kind"), enumeration)
+        ));
+    }
+}
+
+// Push a function, and every overload of it. Teal has no literal type, so an overload with the same parameter
+// list as an earlier shape is left out.
+fn push_teal_function(
+    buffer: &mut String,
+    function: &Value,
+    class: Option<&str>,
+    enumeration: &[String],
+    level: usize,
+) {
+    let generic = get_array(function, "generic")
+        .iter()
+        .filter_map(Value::as_str)
+        .collect::<Vec<_>>()
+        .join(", ");
+    let generic = if generic.is_empty() {
+        generic
+    } else {
+        format!("<{generic}>")
+    };
+
+    let mut list: Vec<Vec<String>> = Vec::new();
+
+    for shape in std::iter::once(function).chain(get_array(function, "overload")) {
+        let mut parameter: Vec<String> = class
+            .map(|class| format!("self: {class}"))
+            .into_iter()
+            .collect();
+        parameter.extend(get_teal_parameter(shape, enumeration));
+
+        let check: Vec<String> = parameter
+            .iter()
+            .map(|value| value.replace("?:", ":"))
+            .collect();
+
+        if list.contains(&check) {
+            continue;
+        }
+
+        buffer.push_str(&format!(
+            "{}{}: function{generic}({}){}\n",
+            INDENT.repeat(level),
+            get_text(function, "name"),
+            parameter.join(", "),
+            get_teal_result(shape, enumeration)
+        ));
+        list.push(check);
+    }
+}
+
+fn get_teal_parameter(function: &Value, enumeration: &[String]) -> Vec<String> {
+    get_array(function, "parameter")
+        .iter()
+        .map(|parameter| {
+            let name = get_text(parameter, "name");
+            let kind = get_teal_kind(&get_text(parameter, "kind"), enumeration);
+
+            if name == "..." {
+                format!("...: {kind}")
+            } else if parameter["optional"].as_bool().unwrap_or_default() {
+                format!("{name}?: {kind}")
+            } else {
+                format!("{name}: {kind}")
+            }
+        })
+        .collect()
+}
+
+fn get_teal_result(function: &Value, enumeration: &[String]) -> String {
+    let result: Vec<String> = get_array(function, "result")
+        .iter()
+        .map(|result| {
+            let kind = get_teal_kind(&get_text(result, "kind"), enumeration);
+
+            if get_text(result, "name") == "..." {
+                format!("{kind}...")
+            } else {
+                kind
+            }
+        })
+        .collect();
+
+    if result.is_empty() {
+        String::new()
+    } else {
+        format!(": {}", result.join(", "))
+    }
+}
+
+fn get_teal_kind(kind: &str, enumeration: &[String]) -> String {
+    // A function kind with a result list, such as "fun(...): T".
+    if let Some(result) = kind.strip_prefix("fun(...)") {
+        return match result.strip_prefix(": ") {
+            Some(result) => format!(
+                "function(...: any): {}",
+                result
+                    .split(", ")
+                    .map(|kind| get_teal_kind(kind, enumeration))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            None => "function(...: any)".to_string(),
+        };
+    }
+
+    kind.split('|')
+        .map(|kind| match kind {
+            kind if enumeration.iter().any(|name| name == kind) => "integer".to_string(),
+            "table" => "{any:any}".to_string(),
+            "function" => "function(...: any): any...".to_string(),
+            "cdata" => "any".to_string(),
+            "true" | "false" => "boolean".to_string(),
+            kind if kind.starts_with('"') => "string".to_string(),
+            kind if kind.parse::<f64>().is_ok() => "number".to_string(),
+            kind => kind.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join(" | ")
+}
+
+//================================================================
+
+// Create the TypeScriptToLua declaration file.
+pub fn get_type_script(description: &Value) -> String {
+    let mut buffer = String::from("declare namespace flak {\n");
+
+    for item in get_array(description, "record") {
+        push_comment(&mut buffer, "//", item, 1);
+        buffer.push_str(&format!(
+            "{INDENT}interface {} {{\n",
+            get_text(item, "name")
+        ));
+
+        for field in get_array(item, "field") {
+            let name = get_text(field, "name");
+            let kind = get_type_script_kind(&get_text(field, "kind"));
+
+            push_comment(&mut buffer, "//", field, 2);
+
+            if field["optional"].as_bool().unwrap_or_default() {
+                buffer.push_str(&format!("{INDENT}{INDENT}{name}?: {kind};\n"));
+            } else {
+                buffer.push_str(&format!("{INDENT}{INDENT}{name}: {kind};\n"));
+            }
+        }
+
+        buffer.push_str(&format!("{INDENT}}}\n\n"));
+    }
+
+    for item in get_array(description, "enumeration") {
+        let name = get_text(item, "name");
+        let table = get_text(item, "table");
+
+        push_comment(&mut buffer, "//", item, 1);
+        buffer.push_str(&format!("{INDENT}enum {table} {{\n"));
+
+        for variant in get_array(item, "variant") {
+            buffer.push_str(&format!(
+                "{INDENT}{INDENT}{} = {},\n",
+                get_text(variant, "name"),
+                variant["value"]
+            ));
+        }
+
+        buffer.push_str(&format!("{INDENT}}}\n"));
+
+        // The table name of an enumeration may differ from its type name (such as "key" and "Key").
+        if name != table {
+            buffer.push_str(&format!("{INDENT}type {name} = {table};\n"));
+        }
+
+        buffer.push('\n');
+    }
+
+    for item in get_array(description, "class") {
+        let name = get_text(item, "name");
+
+        push_comment(&mut buffer, "//", item, 1);
+        buffer.push_str(&format!("{INDENT}interface {name} {{\n"));
+
+        // An interface can be declared twice, so a class with the same name as a record is merged with it.
+        for method in get_array(item, "method") {
+            let operator = get_text(method, "operator");
+
+            if operator.is_empty() {
+                push_comment(&mut buffer, "//", method, 2);
+                push_type_script_function(&mut buffer, method, "", false, 2);
+            } else if let Some((_, kind, unary)) = TYPE_SCRIPT_OPERATOR
+                .iter()
+                .find(|(name, _, _)| *name == operator)
+            {
+                let mut kind_list: Vec<String> = Vec::new();
+
+                if !unary {
+                    kind_list.extend(
+                        get_array(method, "parameter")
+                            .iter()
+                            .map(|parameter| get_type_script_kind(&get_text(parameter, "kind"))),
+                    );
+                }
+
+                kind_list.push(get_type_script_result(method));
+
+                push_comment(&mut buffer, "//", method, 2);
+                buffer.push_str(&format!(
+                    "{INDENT}{INDENT}{}: {kind}<{}>;\n",
+                    get_text(method, "name"),
+                    kind_list.join(", ")
+                ));
+            }
+        }
+
+        buffer.push_str(&format!("{INDENT}}}\n\n"));
+    }
+
+    for (name, module) in Module::new(description) {
+        push_type_script_module(&mut buffer, &name, &module, 1);
+    }
+
+    buffer = format!("{}\n}}\n", buffer.trim_end());
+    buffer
+}
+
+fn push_type_script_module(buffer: &mut String, name: &str, module: &Module, level: usize) {
+    let indent = INDENT.repeat(level);
+
+    if !module.info.is_empty() {
+        buffer.push_str(&format!("{indent}// {}\n", module.info));
+    }
+
+    buffer.push_str(&format!("{indent}namespace {name} {{\n"));
+
+    for (name, child) in &module.child {
+        push_type_script_module(buffer, name, child, level + 1);
+    }
+
+    for function in &module.function {
+        // Module functions are called with ".", so they must not take a "self" argument.
+        push_comment(buffer, "//", function, level + 1);
+        push_type_script_function(buffer, function, "function ", true, level + 1);
+    }
+
+    buffer.push_str(&format!("{indent}}}\n\n"));
+}
+
+// Push a function, with every overload of it before the function itself.
+fn push_type_script_function(
+    buffer: &mut String,
+    function: &Value,
+    prefix: &str,
+    void: bool,
+    level: usize,
+) {
+    let generic = get_array(function, "generic")
+        .iter()
+        .filter_map(Value::as_str)
+        .collect::<Vec<_>>()
+        .join(", ");
+    let generic = if generic.is_empty() {
+        generic
+    } else {
+        format!("<{generic}>")
+    };
+
+    for shape in get_array(function, "overload")
+        .iter()
+        .chain(std::iter::once(function))
+    {
+        let mut parameter: Vec<String> =
+            void.then(|| "this: void".to_string()).into_iter().collect();
+        parameter.extend(get_type_script_parameter(shape));
+
+        buffer.push_str(&format!(
+            "{}{prefix}{}{generic}({}): {};\n",
+            INDENT.repeat(level),
+            get_text(function, "name"),
+            parameter.join(", "),
+            get_type_script_result(shape)
+        ));
+    }
+}
+
+fn get_type_script_parameter(function: &Value) -> Vec<String> {
+    get_array(function, "parameter")
+        .iter()
+        .map(|parameter| {
+            let name = get_text(parameter, "name");
+            let kind = get_type_script_kind(&get_text(parameter, "kind"));
+
+            if name == "..." {
+                format!("...argument: ({kind})[]")
+            } else if parameter["optional"].as_bool().unwrap_or_default() {
+                format!("{name}?: {kind}")
+            } else {
+                format!("{name}: {kind}")
+            }
+        })
+        .collect()
+}
+
+fn get_type_script_result(function: &Value) -> String {
+    let list = get_array(function, "result");
+    let result: Vec<String> = list
+        .iter()
+        .map(|result| {
+            let kind = get_type_script_kind(&get_text(result, "kind"));
+
+            if get_text(result, "name") == "..." {
+                format!("...({kind})[]")
+            } else if result["optional"].as_bool().unwrap_or_default() {
+                format!("{kind} | undefined")
+            } else {
+                kind
+            }
+        })
+        .collect();
+
+    // A variadic result is always a multi-return, even if it is the only result.
+    let variadic = list.iter().any(|result| get_text(result, "name") == "...");
+
+    match result.len() {
+        0 => "void".to_string(),
+        1 if !variadic => result[0].clone(),
+        _ => format!("LuaMultiReturn<[{}]>", result.join(", ")),
+    }
+}
+
+fn get_type_script_kind(kind: &str) -> String {
+    // A function kind with a result list, such as "fun(...): T".
+    if let Some(result) = kind.strip_prefix("fun(...)") {
+        let result: Vec<String> = result
+            .strip_prefix(": ")
+            .map(|result| result.split(", ").map(get_type_script_kind).collect())
+            .unwrap_or_default();
+
+        return match result.len() {
+            0 => "(...argument: any[]) => void".to_string(),
+            1 => format!("(...argument: any[]) => {}", result[0]),
+            _ => format!(
+                "(...argument: any[]) => LuaMultiReturn<[{}]>",
+                result.join(", ")
+            ),
+        };
+    }
+
+    kind.split('|')
+        .map(|kind| match kind {
+            "table" => "LuaTable",
+            "function" => "(...argument: any[]) => any",
+            "cdata" => "any",
+            kind => kind,
+        })
+        .collect::<Vec<_>>()
+        .join(" | ")
+}
+
+//================================================================
+
+fn push_comment(buffer: &mut String, comment: &str, item: &Value, level: usize) {
+    for line in get_text(item, "info").lines() {
+        buffer.push_str(&format!(
+            "{}{comment} {}\n",
+            INDENT.repeat(level),
+            line.trim()
+        ));
+    }
+}
+
+//================================================================
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::meta::document::get_description;
+    use serde_json::json;
+
+    fn get_sample() -> Value {
+        get_description(vec![
+            json!({ "kind": "module", "name": "sample", "info": "Sample API." }),
+            json!({ "kind": "module", "name": "sample.child", "info": "" }),
+            json!({
+                "kind": "function",
+                "from": "sample",
+                "name": "new",
+                "method": false,
+                "info": "Create a new thing.",
+                "parameter": [{ "name": "mode", "kind": "Mode", "info": "Mode.", "optional": true }],
+                "result": [{ "name": "thing", "kind": "Thing", "info": "Thing." }],
+            }),
+            json!({
+                "kind": "function",
+                "from": "sample.child",
+                "name": "get",
+                "method": false,
+                "info": "Get every value.",
+                "parameter": [{ "name": "call", "kind": "function", "info": "Call." }],
+                "result": [{ "name": "...", "kind": "number", "info": "Value list." }],
+            }),
+            json!({ "kind": "class", "name": "Thing", "info": "Thing class." }),
+            json!({
+                "kind": "function",
+                "from": "Thing",
+                "name": "add",
+                "method": true,
+                "operator": "add",
+                "info": "Add two things.",
+                "parameter": [{ "name": "other", "kind": "Thing", "info": "Other thing." }],
+                "result": [{ "name": "thing", "kind": "Thing", "info": "Thing." }],
+            }),
+            json!({
+                "kind": "function",
+                "from": "Thing",
+                "name": "get_point",
+                "method": true,
+                "info": "Get the point.",
+                "result": [{ "name": "point", "kind": "Point", "info": "Point." }],
+            }),
+            json!({
+                "kind": "enumeration",
+                "name": "Mode",
+                "table": "mode",
+                "info": "Mode enumeration.",
+                "variant": [{ "name": "FAST", "value": 0, "info": "" }, { "name": "SLOW", "value": 1, "info": "" }],
+            }),
+            json!({
+                "kind": "record",
+                "name": "Point",
+                "info": "Point record.",
+                "field": [
+                    { "name": "x", "kind": "number", "info": "X coordinate." },
+                    { "name": "mode", "kind": "Mode", "info": "Mode.", "optional": true },
+                ],
+            }),
+        ])
+    }
+
+    #[test]
+    fn teal() {
+        assert_eq!(
+            get_teal(&get_sample()),
+            r#"global record flak
+    -- Mode enumeration.
+    record ModeTable
+        FAST: integer
+        SLOW: integer
+    end
+    mode: ModeTable
+
+    -- Point record.
+    record Point
+        -- X coordinate.
+        x: number
+        -- Mode.
+        mode: integer
+    end
+
+    -- Thing class.
+    record Thing
+        -- Add two things.
+        metamethod __add: function(self: Thing, other: Thing): Thing
+        -- Get the point.
+        get_point: function(self: Thing): Point
+    end
+
+    -- Sample API.
+    record sample_module
+        record child_module
+            -- Get every value.
+            get: function(call: function(...: any): any...): number...
+        end
+        child: child_module
+
+        -- Create a new thing.
+        new: function(mode?: integer): Thing
+    end
+    sample: sample_module
+end
+"#
+        );
+    }
+
+    #[test]
+    fn type_script() {
+        assert_eq!(
+            get_type_script(&get_sample()),
+            r#"declare namespace flak {
+    // Point record.
+    interface Point {
+        // X coordinate.
+        x: number;
+        // Mode.
+        mode?: Mode;
+    }
+
+    // Mode enumeration.
+    enum mode {
+        FAST = 0,
+        SLOW = 1,
+    }
+    type Mode = mode;
+
+    // Thing class.
+    interface Thing {
+        // Add two things.
+        add: LuaAdditionMethod<Thing, Thing>;
+        // Get the point.
+        get_point(): Point;
+    }
+
+    // Sample API.
+    namespace sample {
+        namespace child {
+            // Get every value.
+            function get(this: void, call: (...argument: any[]) => any): LuaMultiReturn<[...(number)[]]>;
+        }
+
+        // Create a new thing.
+        function new(this: void, mode?: Mode): Thing;
+    }
+}
+"#
+        );
+    }
+}
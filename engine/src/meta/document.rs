@@ -0,0 +1,623 @@
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+//================================================================
+
+// A piece of text within a block.
+enum Inline {
+    Text(String),
+    Code(String),
+    // Link to another page, by name.
+    Link(String, String),
+}
+
+enum Block {
+    Heading(usize, Vec<Inline>),
+    Paragraph(Vec<Inline>),
+    List(Vec<Vec<Inline>>),
+}
+
+pub struct Page {
+    name: String,
+    title: String,
+    block: Vec<Block>,
+}
+
+impl Page {
+    fn new(name: &str, title: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            title: title.to_string(),
+            block: vec![Block::Heading(1, vec![Inline::Text(title.to_string())])],
+        }
+    }
+
+    pub fn write(&self, path: &str) -> std::io::Result<()> {
+        std::fs::create_dir_all(format!("{path}/markdown"))?;
+        std::fs::create_dir_all(format!("{path}/html"))?;
+
+        std::fs::write(
+            format!("{path}/markdown/{}.md", self.name),
+            self.get_markdown(),
+        )?;
+        std::fs::write(format!("{path}/html/{}.html", self.name), self.get_html())
+    }
+
+    fn get_markdown(&self) -> String {
+        let mut buffer = String::new();
+
+        for block in &self.block {
+            match block {
+                Block::Heading(level, text) => {
+                    buffer.push_str(&format!(
+                        "{} {}\n\n",
+                        "#".repeat(*level),
+                        Self::get_inline_markdown(text)
+                    ));
+                }
+                Block::Paragraph(text) => {
+                    buffer.push_str(&format!("{}\n\n", Self::get_inline_markdown(text)));
+                }
+                Block::List(list) => {
+                    for text in list {
+                        buffer.push_str(&format!("- {}\n", Self::get_inline_markdown(text)));
+                    }
+
+                    buffer.push('\n');
+                }
+            }
+        }
+
+        format!("{}\n", buffer.trim_end())
+    }
+
+    fn get_html(&self) -> String {
+        let mut buffer = format!(
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n</head>\n<body>\n",
+            Self::get_escape(&self.title)
+        );
+
+        for block in &self.block {
+            match block {
+                Block::Heading(level, text) => {
+                    buffer.push_str(&format!(
+                        "<h{level}>{}</h{level}>\n",
+                        Self::get_inline_html(text)
+                    ));
+                }
+                Block::Paragraph(text) => {
+                    buffer.push_str(&format!("<p>{}</p>\n", Self::get_inline_html(text)));
+                }
+                Block::List(list) => {
+                    buffer.push_str("<ul>\n");
+
+                    for text in list {
+                        buffer.push_str(&format!("<li>{}</li>\n", Self::get_inline_html(text)));
+                    }
+
+                    buffer.push_str("</ul>\n");
+                }
+            }
+        }
+
+        buffer.push_str("</body>\n</html>\n");
+        buffer
+    }
+
+    fn get_inline_markdown(text: &[Inline]) -> String {
+        text.iter()
+            .map(|inline| match inline {
+                Inline::Text(text) => text.clone(),
+                Inline::Code(text) => format!("`{text}`"),
+                Inline::Link(text, page) => format!("[{text}]({page}.md)"),
+            })
+            .collect()
+    }
+
+    fn get_inline_html(text: &[Inline]) -> String {
+        text.iter()
+            .map(|inline| match inline {
+                Inline::Text(text) => Self::get_escape(text),
+                Inline::Code(text) => format!("<code>{}</code>", Self::get_escape(text)),
+                Inline::Link(text, page) => {
+                    format!("<a href=\"{page}.html\">{}</a>", Self::get_escape(text))
+                }
+            })
+            .collect()
+    }
+
+    fn get_escape(text: &str) -> String {
+        text.replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;")
+            .replace('"', "&quot;")
+    }
+}
+
+//================================================================
+
+// Group every item description into the API description, with every function in its module or class.
+pub fn get_description(list: Vec<Value>) -> Value {
+    let mut module: BTreeMap<String, Value> = BTreeMap::new();
+    let mut class: BTreeMap<String, Value> = BTreeMap::new();
+    let mut enumeration: BTreeMap<String, Value> = BTreeMap::new();
+    let mut record: BTreeMap<String, Value> = BTreeMap::new();
+    let mut function: Vec<Value> = Vec::new();
+
+    for mut item in list {
+        let name = get_text(&item, "name");
+
+        match item["kind"].as_str() {
+            Some("module") => {
+                item["function"] = Value::Array(Vec::new());
+                module.insert(name, item);
+            }
+            Some("class") => {
+                item["method"] = Value::Array(Vec::new());
+                class.insert(name, item);
+            }
+            Some("enumeration") => {
+                enumeration.insert(name, item);
+            }
+            Some("record") => {
+                record.insert(name, item);
+            }
+            Some("function") => function.push(item),
+            _ => {}
+        }
+    }
+
+    function.sort_by_key(|item| (get_text(item, "from"), get_text(item, "name")));
+
+    for item in function {
+        let from = get_text(&item, "from");
+
+        let (owner, key) = if item["method"].as_bool().unwrap_or_default() {
+            (class.get_mut(&from), "method")
+        } else {
+            (module.get_mut(&from), "function")
+        };
+
+        if let Some(Value::Array(list)) = owner.map(|owner| &mut owner[key]) {
+            list.push(item);
+        }
+    }
+
+    serde_json::json!({
+        "module": module.into_values().collect::<Vec<_>>(),
+        "class": class.into_values().collect::<Vec<_>>(),
+        "enumeration": enumeration.into_values().collect::<Vec<_>>(),
+        "record": record.into_values().collect::<Vec<_>>(),
+    })
+}
+
+// Create every reference page from the API description: an index, and a page per module, class, enumeration and
+// record.
+pub fn get_page(description: &Value) -> Vec<Page> {
+    let module = get_array(description, "module");
+    let class = get_array(description, "class");
+    let enumeration = get_array(description, "enumeration");
+    let record = get_array(description, "record");
+
+    // Every type with a page of its own, which other pages will link to.
+    let mut link: BTreeMap<String, String> = BTreeMap::new();
+
+    for item in class {
+        let name = get_text(item, "name");
+        link.insert(name.clone(), format!("class_{name}"));
+    }
+
+    for item in enumeration {
+        let name = get_text(item, "name");
+        link.insert(name.clone(), format!("enumeration_{name}"));
+    }
+
+    for item in record {
+        let name = get_text(item, "name");
+        link.insert(name.clone(), format!("record_{name}"));
+    }
+
+    let mut list = Vec::new();
+    let mut index = Page::new("index", "flak API");
+
+    //================================================================
+
+    // Sub-modules (such as "input.board") go in the page of their parent module.
+    let mut module_page: BTreeMap<String, Page> = BTreeMap::new();
+
+    for item in module {
+        let name = get_text(item, "name");
+        let root = name.split('.').next().unwrap_or_default().to_string();
+
+        let page = module_page
+            .entry(root.clone())
+            .or_insert_with(|| Page::new(&format!("module_{root}"), &format!("flak.{root}")));
+
+        if name != root {
+            page.block.push(Block::Heading(
+                2,
+                vec![Inline::Text(format!("flak.{name}"))],
+            ));
+        }
+
+        page.block
+            .push(Block::Paragraph(vec![Inline::Text(get_text(item, "info"))]));
+
+        for function in get_array(item, "function") {
+            push_function(page, function, &format!("flak.{name}."), &link);
+        }
+    }
+
+    index
+        .block
+        .push(Block::Heading(2, vec![Inline::Text("Modules".to_string())]));
+    index.block.push(Block::List(
+        module_page
+            .values()
+            .map(|page| vec![Inline::Link(page.title.clone(), page.name.clone())])
+            .collect(),
+    ));
+
+    list.extend(module_page.into_values());
+
+    //================================================================
+
+    let mut index_class = Vec::new();
+
+    for item in class {
+        let name = get_text(item, "name");
+        let mut page = Page::new(&format!("class_{name}"), &name);
+
+        page.block
+            .push(Block::Paragraph(vec![Inline::Text(get_text(item, "info"))]));
+
+        for method in get_array(item, "method") {
+            push_function(&mut page, method, &format!("{name}:"), &link);
+        }
+
+        index_class.push(vec![
+            Inline::Link(name.clone(), page.name.clone()),
+            Inline::Text(format!(": {}", get_text(item, "info"))),
+        ]);
+        list.push(page);
+    }
+
+    index
+        .block
+        .push(Block::Heading(2, vec![Inline::Text("Classes".to_string())]));
+    index.block.push(Block::List(index_class));
+
+    //================================================================
+
+    let mut index_enumeration = Vec::new();
+
+    for item in enumeration {
+        let name = get_text(item, "name");
+        let table = get_text(item, "table");
+        let mut page = Page::new(&format!("enumeration_{name}"), &name);
+
+        page.block
+            .push(Block::Paragraph(vec![Inline::Text(get_text(item, "info"))]));
+        page.block.push(Block::Paragraph(vec![
+            Inline::Text("Table: ".to_string()),
+            Inline::Code(format!("flak.{table}")),
+        ]));
+        page.block.push(Block::List(
+            get_array(item, "variant")
+                .iter()
+                .map(|variant| {
+                    let mut text = vec![Inline::Code(format!(
+                        "{} = {}",
+                        get_text(variant, "name"),
+                        variant["value"]
+                    ))];
+                    let info = get_text(variant, "info");
+
+                    if !info.is_empty() {
+                        text.push(Inline::Text(format!(": {info}")));
+                    }
+
+                    text
+                })
+                .collect(),
+        ));
+
+        index_enumeration.push(vec![
+            Inline::Link(name.clone(), page.name.clone()),
+            Inline::Text(format!(": {}", get_text(item, "info"))),
+        ]);
+        list.push(page);
+    }
+
+    index.block.push(Block::Heading(
+        2,
+        vec![Inline::Text("Enumerations".to_string())],
+    ));
+    index.block.push(Block::List(index_enumeration));
+
+    //================================================================
+
+    let mut index_record = Vec::new();
+
+    for item in record {
+        let name = get_text(item, "name");
+        let mut page = Page::new(&format!("record_{name}"), &name);
+
+        page.block
+            .push(Block::Paragraph(vec![Inline::Text(get_text(item, "info"))]));
+        page.block
+            .push(Block::Paragraph(vec![Inline::Text("Fields:".to_string())]));
+        page.block.push(Block::List(
+            get_array(item, "field")
+                .iter()
+                .map(|field| get_value(field, &link))
+                .collect(),
+        ));
+
+        index_record.push(vec![
+            Inline::Link(name.clone(), page.name.clone()),
+            Inline::Text(format!(": {}", get_text(item, "info"))),
+        ]);
+        list.push(page);
+    }
+
+    index
+        .block
+        .push(Block::Heading(2, vec![Inline::Text("Records".to_string())]));
+    index.block.push(Block::List(index_record));
+
+    list.push(index);
+    list
+}
+
+//================================================================
+
+fn push_function(page: &mut Page, item: &Value, from: &str, link: &BTreeMap<String, String>) {
+    let parameter = get_array(item, "parameter");
+    let result = get_array(item, "result");
+    let name: Vec<String> = parameter
+        .iter()
+        .map(|value| get_text(value, "name"))
+        .collect();
+
+    // An operator is shown as its meta-method, such as "Vector2 __add(other)".
+    let operator = get_text(item, "operator");
+    let heading = if operator.is_empty() {
+        format!("{from}{}({})", get_text(item, "name"), name.join(", "))
+    } else {
+        format!(
+            "{} __{operator}({})",
+            from.trim_end_matches(':'),
+            name.join(", ")
+        )
+    };
+
+    page.block
+        .push(Block::Heading(3, vec![Inline::Code(heading)]));
+    page.block
+        .push(Block::Paragraph(vec![Inline::Text(get_text(item, "info"))]));
+
+    for (list, title) in [(parameter, "Parameters"), (result, "Results")] {
+        if list.is_empty() {
+            continue;
+        }
+
+        page.block
+            .push(Block::Paragraph(vec![Inline::Text(format!("{title}:"))]));
+        page.block.push(Block::List(
+            list.iter().map(|value| get_value(value, link)).collect(),
+        ));
+    }
+
+    let overload = get_array(item, "overload");
+
+    if !overload.is_empty() {
+        page.block.push(Block::Paragraph(vec![Inline::Text(
+            "Overloads:".to_string(),
+        )]));
+        page.block.push(Block::List(
+            overload
+                .iter()
+                .map(|overload| vec![Inline::Code(get_overload(overload, from, item))])
+                .collect(),
+        ));
+    }
+}
+
+// Get an overload as a signature, such as "flak.data.get_file(path: string, binary: true): table".
+fn get_overload(overload: &Value, from: &str, item: &Value) -> String {
+    let parameter: Vec<String> = get_array(overload, "parameter")
+        .iter()
+        .map(get_signature)
+        .collect();
+    let result: Vec<String> = get_array(overload, "result")
+        .iter()
+        .map(|value| get_text(value, "kind"))
+        .collect();
+
+    let mut text = format!("{from}{}({})", get_text(item, "name"), parameter.join(", "));
+
+    if !result.is_empty() {
+        text.push_str(&format!(": {}", result.join(", ")));
+    }
+
+    text
+}
+
+fn get_signature(value: &Value) -> String {
+    if value["optional"].as_bool().unwrap_or_default() {
+        format!("{}?: {}", get_text(value, "name"), get_text(value, "kind"))
+    } else {
+        format!("{}: {}", get_text(value, "name"), get_text(value, "kind"))
+    }
+}
+
+fn get_value(value: &Value, link: &BTreeMap<String, String>) -> Vec<Inline> {
+    let kind = get_text(value, "kind");
+    let mut text = vec![
+        Inline::Code(get_text(value, "name")),
+        Inline::Text(" (".to_string()),
+    ];
+
+    if let Some(page) = link.get(&kind) {
+        text.push(Inline::Link(kind, page.clone()));
+    } else {
+        text.push(Inline::Text(kind));
+    }
+
+    if value["optional"].as_bool().unwrap_or_default() {
+        text.push(Inline::Text(", optional".to_string()));
+    }
+
+    text.push(Inline::Text(format!("): {}", get_text(value, "info"))));
+    text
+}
+
+pub fn get_text(value: &Value, key: &str) -> String {
+    value[key].as_str().unwrap_or_default().to_string()
+}
+
+pub fn get_array<'a>(value: &'a Value, key: &str) -> &'a [Value] {
+    value[key].as_array().map(Vec::as_slice).unwrap_or_default()
+}
+
+//================================================================
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json::json;
+
+    fn get_sample() -> Value {
+        get_description(vec![
+            json!({ "kind": "module", "name": "sample", "info": "Sample API." }),
+            json!({ "kind": "module", "name": "sample.child", "info": "Sample child API." }),
+            json!({
+                "kind": "function",
+                "from": "sample",
+                "name": "new",
+                "method": false,
+                "info": "Create a new <Thing>.",
+                "parameter": [{ "name": "mode", "kind": "Mode", "info": "Mode.", "optional": true }],
+                "result": [{ "name": "thing", "kind": "Thing", "info": "Thing." }],
+            }),
+            json!({ "kind": "class", "name": "Thing", "info": "Thing class." }),
+            json!({
+                "kind": "function",
+                "from": "Thing",
+                "name": "add",
+                "method": true,
+                "operator": "add",
+                "info": "Add two things.",
+                "parameter": [{ "name": "other", "kind": "Thing", "info": "Other thing." }],
+            }),
+            json!({
+                "kind": "enumeration",
+                "name": "Mode",
+                "table": "mode",
+                "info": "Mode enumeration.",
+                "variant": [{ "name": "FAST", "value": 0, "info": "" }, { "name": "SLOW", "value": 1, "info": "" }],
+            }),
+            json!({
+                "kind": "record",
+                "name": "Point",
+                "info": "Point record.",
+                "field": [{ "name": "x", "kind": "number", "info": "X coordinate." }],
+            }),
+            // A function with no owner is left out.
+            json!({ "kind": "function", "from": "missing", "name": "get", "method": false }),
+        ])
+    }
+
+    fn get_page_by_name(name: &str) -> Page {
+        get_page(&get_sample())
+            .into_iter()
+            .find(|page| page.name == name)
+            .expect("Missing page.")
+    }
+
+    #[test]
+    fn description() {
+        let description = get_sample();
+
+        assert_eq!(get_array(&description, "module").len(), 2);
+        assert_eq!(get_array(&description["module"][0], "function").len(), 1);
+        assert_eq!(get_array(&description["class"][0], "method").len(), 1);
+        assert_eq!(get_array(&description, "enumeration").len(), 1);
+        assert_eq!(get_array(&description, "record").len(), 1);
+    }
+
+    #[test]
+    fn page() {
+        let name: Vec<String> = get_page(&get_sample())
+            .into_iter()
+            .map(|page| page.name)
+            .collect();
+
+        // A sub-module goes in the page of its parent module, and the index is last.
+        assert_eq!(
+            name,
+            [
+                "module_sample",
+                "class_Thing",
+                "enumeration_Mode",
+                "record_Point",
+                "index"
+            ]
+        );
+    }
+
+    #[test]
+    fn markdown() {
+        assert_eq!(
+            get_page_by_name("module_sample").get_markdown(),
+            r#"# flak.sample
+
+Sample API.
+
+### `flak.sample.new(mode)`
+
+Create a new <Thing>.
+
+Parameters:
+
+- `mode` ([Mode](enumeration_Mode.md), optional): Mode.
+
+Results:
+
+- `thing` ([Thing](class_Thing.md)): Thing.
+
+## flak.sample.child
+
+Sample child API.
+"#
+        );
+
+        assert_eq!(
+            get_page_by_name("class_Thing").get_markdown(),
+            r#"# Thing
+
+Thing class.
+
+### `Thing __add(other)`
+
+Add two things.
+
+Parameters:
+
+- `other` ([Thing](class_Thing.md)): Other thing.
+"#
+        );
+    }
+
+    #[test]
+    fn html() {
+        let html = get_page_by_name("module_sample").get_html();
+
+        assert!(html.contains("<title>flak.sample</title>"));
+        assert!(html.contains("<p>Create a new &lt;Thing&gt;.</p>"));
+        assert!(html.contains(
+            "<li><code>thing</code> (<a href=\"class_Thing.html\">Thing</a>): Thing.</li>"
+        ));
+    }
+}
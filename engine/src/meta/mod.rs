@@ -0,0 +1,211 @@
+mod declaration;
+mod document;
+
+//================================================================
+
+const HELP: &str = r#"Usage: engine meta [OPTION]...
+
+Write the Lua language server meta file, the Teal and TypeScriptToLua
+declaration files, and the API reference, from the API of this engine.
+
+Option:
+    --meta FOLDER       Folder for the meta and declaration files. Default: "main".
+    --document FOLDER   Folder for the API reference. Default: "doc".
+    --help              Show this message."#;
+
+// Meta-data of a class, enumeration, function or module, registered by its attribute.
+pub struct Meta {
+    // Unique path of the item, to write every item in the same order.
+    pub path: &'static str,
+    // Lua language server annotation.
+    pub meta: &'static str,
+    // Structured description, for the API reference and declaration files.
+    pub json: &'static str,
+}
+
+inventory::collect!(Meta);
+
+impl Meta {
+    pub const COMMAND: &str = "meta";
+    const META_HEADER: &str = r#"---@meta
+
+---@class flak
+flak = {}
+
+"#;
+    const META_FILE: &str = "meta.lua";
+    const META_PATH: &str = "main";
+    const TEAL_FILE: &str = "flak.d.tl";
+    const TYPE_SCRIPT_FILE: &str = "flak.d.ts";
+    const DOCUMENT_FILE: &str = "api.json";
+    const DOCUMENT_PATH: &str = "doc";
+
+    // Run the "meta" command, with every argument after the command itself.
+    pub fn run(mut list: impl Iterator<Item = String>) -> anyhow::Result<()> {
+        let mut meta_path = Self::META_PATH.to_string();
+        let mut document_path = Self::DOCUMENT_PATH.to_string();
+
+        while let Some(token) = list.next() {
+            match token.as_str() {
+                "--meta" => meta_path = Self::get_value(&mut list, &token)?,
+                "--document" => document_path = Self::get_value(&mut list, &token)?,
+                "--help" => {
+                    println!("{HELP}");
+                    return Ok(());
+                }
+                _ => {
+                    return Err(anyhow::anyhow!(
+                        "Unknown option \"{token}\". Use \"meta --help\" for a list of every option."
+                    ));
+                }
+            }
+        }
+
+        let list = Self::get_list();
+
+        //================================================================
+
+        // Write the meta file.
+        let mut buffer = Self::META_HEADER.to_string();
+
+        // An item may have no annotation (such as the "__eq" operator).
+        for item in list.iter().filter(|item| !item.meta.is_empty()) {
+            buffer.push_str(&format!("{}\n\n", item.meta));
+        }
+
+        std::fs::create_dir_all(&meta_path)?;
+        std::fs::write(format!("{meta_path}/{}", Self::META_FILE), buffer)?;
+
+        //================================================================
+
+        // Write the API description, and the Markdown/HTML reference.
+        let mut description = Vec::new();
+
+        for item in &list {
+            description.push(serde_json::from_str(item.json)?);
+        }
+
+        let description = document::get_description(description);
+
+        std::fs::create_dir_all(&document_path)?;
+        std::fs::write(
+            format!("{document_path}/{}", Self::DOCUMENT_FILE),
+            serde_json::to_string_pretty(&description)?,
+        )?;
+
+        for page in document::get_page(&description) {
+            page.write(&document_path)?;
+        }
+
+        // Write the Teal and TypeScriptToLua declaration files, alongside the meta file.
+        std::fs::write(
+            format!("{meta_path}/{}", Self::TEAL_FILE),
+            declaration::get_teal(&description),
+        )?;
+        std::fs::write(
+            format!("{meta_path}/{}", Self::TYPE_SCRIPT_FILE),
+            declaration::get_type_script(&description),
+        )?;
+
+        println!(
+            "Wrote the meta files to \"{meta_path}\", and the API reference to \"{document_path}\"."
+        );
+
+        Ok(())
+    }
+
+    // Get every registered item, sorted by path, as the registration order may differ from build to build.
+    // A module table must be declared before any function within it.
+    fn get_list() -> Vec<&'static Self> {
+        let mut list: Vec<&Self> = inventory::iter::<Self>.into_iter().collect();
+        list.sort_by_key(|item| (item.path.starts_with("function_"), item.path));
+        list
+    }
+
+    fn get_value(list: &mut impl Iterator<Item = String>, name: &str) -> anyhow::Result<String> {
+        list.next()
+            .ok_or_else(|| anyhow::anyhow!("Missing value for option \"{name}\"."))
+    }
+}
+
+//================================================================
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // Get every function in a module table (and in every table in it, such as "input.board"), as (module, name).
+    fn get_function(table: &mlua::Table, from: &str, list: &mut Vec<(String, String)>) {
+        for pair in table.pairs::<String, mlua::Value>() {
+            let (name, value) = pair.expect("Invalid table entry.");
+
+            match value {
+                mlua::Value::Function(_) => list.push((from.to_string(), name)),
+                mlua::Value::Table(table) => {
+                    let from = if from.is_empty() {
+                        name
+                    } else {
+                        format!("{from}.{name}")
+                    };
+
+                    get_function(&table, &from, list);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    // Every function set in the "flak" table must have meta-data, and every module function in the meta-data
+    // must be set in the "flak" table.
+    #[test]
+    fn every_export_has_meta() {
+        let mut function = Vec::new();
+        let mut path = Vec::new();
+
+        for item in Meta::get_list() {
+            let json: serde_json::Value =
+                serde_json::from_str(item.json).expect("Invalid description.");
+
+            assert!(
+                !path.contains(&item.path),
+                "Duplicate meta \"{}\".",
+                item.path
+            );
+            path.push(item.path);
+
+            if json["kind"] == "function" && json["method"] == false {
+                function.push((
+                    json["from"].as_str().unwrap_or_default().to_string(),
+                    json["name"].as_str().unwrap_or_default().to_string(),
+                ));
+            }
+        }
+
+        // Set every module, as the engine does, rather than going by the exported function list, so that a module
+        // which is never set (or which never sets a table) is caught as well.
+        let lua = mlua::Lua::new();
+        let global = lua.create_table().expect("Error creating table.");
+
+        crate::module::set_global(&lua, &global, true).expect("Error setting window module.");
+        crate::module::set_global(&lua, &global, false).expect("Error setting module.");
+
+        let mut export = Vec::new();
+        get_function(&global, "", &mut export);
+
+        assert!(!export.is_empty(), "No function is exported.");
+
+        for (from, name) in &export {
+            assert!(
+                function.contains(&(from.clone(), name.clone())),
+                "The function \"{from}.{name}\" has no meta-data."
+            );
+        }
+
+        for (from, name) in &function {
+            assert!(
+                export.contains(&(from.clone(), name.clone())),
+                "The function \"{from}.{name}\" has meta-data, but is not set in the \"flak\" table."
+            );
+        }
+    }
+}
@@ -1,10 +1,12 @@
+use crate::module::data::*;
 use crate::module::general::*;
 use engine_macro::*;
 
 //================================================================
 
 use mlua::prelude::*;
-use std::io::Read;
+use std::io::{Read, Seek, Write};
+use std::path::PathBuf;
 
 //================================================================
 
@@ -13,28 +15,101 @@ use std::io::Read;
 pub fn set_global(lua: &mlua::Lua, global: &mlua::Table) -> anyhow::Result<()> {
     let archive = lua.create_table()?;
 
-    archive.set("new", lua.create_function(self::Archive::new)?)?;
+    Export::set_table(lua, "archive", &archive)?;
 
     global.set("archive", archive)?;
 
+    CompressionKind::set_global(lua, global)?;
+
     Ok(())
 }
 
 //================================================================
 
+#[enumeration(info = "Compression kind.")]
+#[derive(Clone, Copy)]
+pub enum CompressionKind {
+    /// No compression.
+    Store,
+    /// Deflate compression.
+    Deflate,
+    /// Bzip2 compression.
+    Bzip2,
+    /// Zstandard compression.
+    Zstd,
+}
+
+impl CompressionKind {
+    fn get_method(self) -> zip::CompressionMethod {
+        match self {
+            Self::Store => zip::CompressionMethod::Stored,
+            Self::Deflate => zip::CompressionMethod::Deflated,
+            Self::Bzip2 => zip::CompressionMethod::Bzip2,
+            Self::Zstd => zip::CompressionMethod::Zstd,
+        }
+    }
+}
+
+//================================================================
+
+// Key of every AES-encrypted archive entry, supplied by the host. Either embedded at build time, or set from the
+// "key" field in "info" (in which case the entry module itself can not be encrypted).
+pub struct ArchiveKey {
+    key: String,
+}
+
+impl ArchiveKey {
+    pub const BUILD_KEY: Option<&str> = option_env!("FLAK_ARCHIVE_KEY");
+
+    pub fn new(key: String) -> Self {
+        Self { key }
+    }
+
+    pub fn get(lua: &mlua::Lua) -> Option<String> {
+        if let Some(key) = lua.app_data_ref::<Self>() {
+            Some(key.key.clone())
+        } else {
+            Self::BUILD_KEY.map(str::to_string)
+        }
+    }
+}
+
+// Find an entry by path, decrypting it with the key if it is AES-encrypted. An entry that is not encrypted is read
+// as is.
+pub fn get_entry<'a, R: Read + Seek>(
+    archive: &'a mut zip::ZipArchive<R>,
+    path: &str,
+    key: Option<&str>,
+) -> zip::result::ZipResult<zip::read::ZipFile<'a, R>> {
+    let index = archive
+        .index_for_path(path)
+        .ok_or(zip::result::ZipError::FileNotFound)?;
+
+    archive.by_index_with_options(
+        index,
+        zip::ZipReadOptions::new().password(key.map(str::as_bytes)),
+    )
+}
+
+//================================================================
+
 #[class(info = "Archive class.")]
 pub struct Archive {
     inner: zip::ZipArchive<std::fs::File>,
+    key: Option<String>,
 }
 
+#[export]
 impl Archive {
     pub fn borrow_file(path: &str, user: mlua::AnyUserData) -> mlua::Result<(Vec<u8>, String)> {
         if let Ok(mut archive) = user.borrow_mut::<Self>() {
+            let archive = &mut *archive;
             let token: Vec<&str> = path.split(".").collect();
 
             if let Some(extension) = token.get(1) {
                 let extension = format!(".{}", extension);
-                let mut find = map_error(archive.inner.by_path(path))?;
+                let mut find =
+                    map_error(get_entry(&mut archive.inner, path, archive.key.as_deref()))?;
                 let mut file = Vec::new();
                 find.read_to_end(&mut file)?;
 
@@ -55,17 +130,26 @@ impl Archive {
         from = "archive",
         info = "Create a new Archive resource.",
         parameter(name = "path", info = "Path to archive.", kind = "string"),
+        parameter(
+            name = "key",
+            info = "Key for every AES-encrypted entry. Default: the key given by the host, if any.",
+            kind = "string",
+            optional = true
+        ),
         result(
             name = "archive",
             info = "Archive resource.",
             kind(user_data(name = "Archive"))
         )
     )]
-    fn new(_: &mlua::Lua, path: String) -> mlua::Result<Self> {
-        let inner = std::fs::File::open(path)?;
+    fn new(lua: &mlua::Lua, (path, key): (String, Option<String>)) -> mlua::Result<Self> {
+        let inner = std::fs::File::open(DataRoot::get_path(lua, &path)?)?;
         let inner = map_error(zip::ZipArchive::new(inner))?;
 
-        Ok(Self { inner })
+        Ok(Self {
+            inner,
+            key: key.or_else(|| ArchiveKey::get(lua)),
+        })
     }
 
     #[method(
@@ -93,7 +177,17 @@ impl Archive {
         result(
             name = "data",
             info = "File data.",
-            kind(user_data(name = "table|string"))
+            kind(union(kind = "table", kind = "string"))
+        ),
+        overload(
+            parameter(name = "path", kind = "string"),
+            parameter(name = "binary", kind(literal(value = "true"))),
+            result(name = "data", kind = "table")
+        ),
+        overload(
+            parameter(name = "path", kind = "string"),
+            parameter(name = "binary", kind(literal(value = "false"))),
+            result(name = "data", kind = "string")
         )
     )]
     fn get_file(
@@ -101,7 +195,7 @@ impl Archive {
         this: &mut Self,
         (path, binary): (String, bool),
     ) -> mlua::Result<mlua::Value> {
-        let mut find = map_error(this.inner.by_path(path))?;
+        let mut find = map_error(get_entry(&mut this.inner, &path, this.key.as_deref()))?;
 
         if binary {
             let mut file = Vec::new();
@@ -127,16 +221,16 @@ impl Archive {
             optional = true
         )
     )]
-    fn get_kind(_: &mlua::Lua, this: &mut Self, path: String) -> mlua::Result<Option<usize>> {
-        let find = this.inner.by_path(path);
+    fn get_kind(_: &mlua::Lua, this: &mut Self, path: String) -> mlua::Result<Option<PathKind>> {
+        let find = get_entry(&mut this.inner, &path, this.key.as_deref());
 
         if let Ok(find) = find {
             if find.is_file() {
-                return Ok(Some(0));
+                return Ok(Some(PathKind::File));
             } else if find.is_dir() {
-                return Ok(Some(1));
+                return Ok(Some(PathKind::Folder));
             } else if find.is_symlink() {
-                return Ok(Some(2));
+                return Ok(Some(PathKind::Link));
             }
         }
 
@@ -144,11 +238,282 @@ impl Archive {
     }
 }
 
-impl mlua::UserData for Archive {
-    #[rustfmt::skip]
-    fn add_methods<M: mlua::UserDataMethods<Self>>(method: &mut M) {
-        method.add_method("get_list",     Self::get_list);
-        method.add_method_mut("get_file", Self::get_file);
-        method.add_method_mut("get_kind", Self::get_kind);
+//================================================================
+
+// An entry of an archive writer, kept in memory until the archive is finished.
+enum ArchiveEntry {
+    // Entry of the archive being rebuilt, by index. Copied as-is, without compressing it again.
+    Source(usize),
+    // File data, compression, and key to encrypt it with.
+    File(Vec<u8>, CompressionKind, Option<String>),
+    Folder,
+}
+
+#[class(info = "Archive writer class. Nothing is written to disk until the archive is finished.")]
+pub struct ArchiveWriter {
+    path: PathBuf,
+    // Archive being rebuilt, if any.
+    source: Option<zip::ZipArchive<std::fs::File>>,
+    entry: Vec<(String, ArchiveEntry)>,
+    // Key to encrypt every new file with, if any.
+    key: Option<String>,
+    finish: bool,
+}
+
+impl ArchiveWriter {
+    fn check_finish(&self, call: &str) -> mlua::Result<()> {
+        if self.finish {
+            Err(mlua::Error::runtime(format!(
+                "ArchiveWriter:{call}(): Archive is already finished."
+            )))
+        } else {
+            Ok(())
+        }
+    }
+
+    fn set_entry(&mut self, path: String, entry: ArchiveEntry) {
+        if let Some(find) = self.entry.iter_mut().find(|(name, _)| *name == path) {
+            find.1 = entry;
+        } else {
+            self.entry.push((path, entry));
+        }
+    }
+
+    fn write(&mut self, path: &PathBuf) -> mlua::Result<()> {
+        let mut writer = zip::ZipWriter::new(std::fs::File::create(path)?);
+
+        for (name, entry) in &self.entry {
+            match entry {
+                ArchiveEntry::Source(index) => {
+                    if let Some(source) = &mut self.source {
+                        map_error(writer.raw_copy_file(map_error(source.by_index_raw(*index))?))?;
+                    }
+                }
+                ArchiveEntry::File(data, compression, key) => {
+                    let mut option = zip::write::SimpleFileOptions::default()
+                        .compression_method(compression.get_method())
+                        .large_file(data.len() as u64 >= u32::MAX as u64);
+
+                    if let Some(key) = key {
+                        option = option.with_aes_encryption(zip::AesMode::Aes256, key);
+                    }
+
+                    map_error(writer.start_file(name, option))?;
+                    writer.write_all(data)?;
+                }
+                ArchiveEntry::Folder => {
+                    map_error(
+                        writer.add_directory(name, zip::write::SimpleFileOptions::default()),
+                    )?;
+                }
+            }
+        }
+
+        map_error(writer.finish())?;
+
+        Ok(())
+    }
+}
+
+#[export]
+impl ArchiveWriter {
+    #[function(
+        from = "archive",
+        info = "Create a new, empty archive. Any archive at the path will be replaced once the archive is finished.",
+        parameter(name = "path", info = "Path to archive.", kind = "string"),
+        result(
+            name = "archive",
+            info = "Archive writer.",
+            kind(user_data(name = "ArchiveWriter"))
+        )
+    )]
+    fn create(lua: &mlua::Lua, path: String) -> mlua::Result<Self> {
+        Ok(Self {
+            path: DataRoot::get_path(lua, &path)?,
+            source: None,
+            entry: Vec::new(),
+            key: None,
+            finish: false,
+        })
+    }
+
+    #[function(
+        from = "archive",
+        info = "Update an archive, starting with every entry in it. The archive is rebuilt in place once the archive is finished.",
+        parameter(name = "path", info = "Path to archive.", kind = "string"),
+        result(
+            name = "archive",
+            info = "Archive writer.",
+            kind(user_data(name = "ArchiveWriter"))
+        )
+    )]
+    fn update(lua: &mlua::Lua, path: String) -> mlua::Result<Self> {
+        let path = DataRoot::get_path(lua, &path)?;
+        let source = map_error(zip::ZipArchive::new(std::fs::File::open(&path)?))?;
+        let entry = (0..source.len())
+            .filter_map(|index| {
+                source
+                    .name_for_index(index)
+                    .map(|name| (name.to_string(), ArchiveEntry::Source(index)))
+            })
+            .collect();
+
+        Ok(Self {
+            path,
+            source: Some(source),
+            entry,
+            key: None,
+            finish: false,
+        })
+    }
+
+    #[method(
+        from = "ArchiveWriter",
+        info = "Get a full list of every entry in the archive.",
+        result(
+            name = "file_list",
+            info = "Table array of every entry in the archive.",
+            kind = "table"
+        )
+    )]
+    fn get_list(_: &mlua::Lua, this: &Self, _: ()) -> mlua::Result<Vec<String>> {
+        Ok(this.entry.iter().map(|(name, _)| name.clone()).collect())
+    }
+
+    #[method(
+        from = "ArchiveWriter",
+        info = "Set the data of a file. Any entry with the same path will be replaced.",
+        parameter(name = "path", info = "Path to file.", kind = "string"),
+        parameter(
+            name = "data",
+            info = "File data, as a string, or as a table array of every byte.",
+            kind(union(kind = "string", kind = "table"))
+        ),
+        parameter(
+            name = "compression",
+            info = "Compression kind. Default: CompressionKind.Deflate.",
+            kind(user_data(name = "CompressionKind")),
+            optional = true
+        )
+    )]
+    fn set_file(
+        lua: &mlua::Lua,
+        this: &mut Self,
+        (path, data, compression): (String, mlua::Value, Option<CompressionKind>),
+    ) -> mlua::Result<()> {
+        this.check_finish("set_file")?;
+
+        let data = match data {
+            mlua::Value::String(data) => data.as_bytes().to_vec(),
+            data => Vec::<u8>::from_lua(data, lua)?,
+        };
+
+        this.set_entry(
+            path,
+            ArchiveEntry::File(
+                data,
+                compression.unwrap_or(CompressionKind::Deflate),
+                this.key.clone(),
+            ),
+        );
+
+        Ok(())
+    }
+
+    #[method(
+        from = "ArchiveWriter",
+        info = "Set the key to encrypt every file set from now on with (AES-256). Any entry from an updated archive is kept as is.",
+        parameter(
+            name = "key",
+            info = "Key. If missing, every file set from now on will not be encrypted.",
+            kind = "string",
+            optional = true
+        )
+    )]
+    fn set_key(_: &mlua::Lua, this: &mut Self, key: Option<String>) -> mlua::Result<()> {
+        this.check_finish("set_key")?;
+
+        this.key = key;
+
+        Ok(())
+    }
+
+    #[method(
+        from = "ArchiveWriter",
+        info = "Add a folder.",
+        parameter(name = "path", info = "Path to folder.", kind = "string")
+    )]
+    fn add_directory(_: &mlua::Lua, this: &mut Self, path: String) -> mlua::Result<()> {
+        this.check_finish("add_directory")?;
+
+        // A folder entry always ends with a slash.
+        let path = format!("{}/", path.trim_end_matches('/'));
+
+        this.set_entry(path, ArchiveEntry::Folder);
+
+        Ok(())
+    }
+
+    #[method(
+        from = "ArchiveWriter",
+        info = "Remove a file, or a folder along with every entry within it.",
+        parameter(name = "path", info = "Path to file or folder.", kind = "string"),
+        result(
+            name = "remove",
+            info = "True if any entry was removed.",
+            kind = "boolean"
+        )
+    )]
+    fn remove(_: &mlua::Lua, this: &mut Self, path: String) -> mlua::Result<bool> {
+        this.check_finish("remove")?;
+
+        let folder = format!("{}/", path.trim_end_matches('/'));
+        let count = this.entry.len();
+
+        this.entry
+            .retain(|(name, _)| *name != path && !name.starts_with(&folder));
+
+        Ok(this.entry.len() != count)
+    }
+
+    #[method(
+        from = "ArchiveWriter",
+        info = "Write the archive to disk. The archive writer can not be used after this."
+    )]
+    fn finish(_: &mlua::Lua, this: &mut Self, _: ()) -> mlua::Result<()> {
+        this.check_finish("finish")?;
+
+        // Write to a temporary file first, as the archive being rebuilt is read from while writing, and a
+        // failed write must not leave a broken archive behind.
+        let mut temporary = this.path.clone().into_os_string();
+        temporary.push(".tmp");
+        let temporary = PathBuf::from(temporary);
+
+        if let Err(error) = this.write(&temporary) {
+            let _ = std::fs::remove_file(&temporary);
+            return Err(error);
+        }
+
+        // Close the archive being rebuilt, as it can not be replaced while open on some platforms. It is opened again
+        // if the rename fails, so that finishing can be tried again.
+        let source = this.source.take().is_some();
+
+        if let Err(error) = std::fs::rename(&temporary, &this.path) {
+            let _ = std::fs::remove_file(&temporary);
+
+            if source {
+                this.source = Some(map_error(zip::ZipArchive::new(std::fs::File::open(
+                    &this.path,
+                )?))?);
+            }
+
+            return Err(error.into());
+        }
+
+        // Only finish once the archive is in place.
+        this.entry.clear();
+        this.finish = true;
+
+        Ok(())
     }
 }
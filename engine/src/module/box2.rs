@@ -0,0 +1,236 @@
+use crate::module::general::*;
+use engine_macro::*;
+
+//================================================================
+
+use raylib::prelude::*;
+
+//================================================================
+
+#[rustfmt::skip]
+#[module(name = "box2", info = "Box2 API.")]
+pub fn set_global(lua: &mlua::Lua, global: &mlua::Table) -> anyhow::Result<()> {
+    let box2 = lua.create_table()?;
+
+    Export::set_table(lua, "box2", &box2)?;
+
+    global.set("box2", box2)?;
+
+    Ok(())
+}
+
+//================================================================
+
+#[class(
+    name = "Box2",
+    info = "Native 2D box. Accepted in place of a Box2 table by every function."
+)]
+#[derive(Clone, Copy)]
+pub struct NativeBox2 {
+    pub inner: Box2,
+}
+
+impl mlua::FromLua for NativeBox2 {
+    fn from_lua(value: mlua::Value, _: &mlua::Lua) -> mlua::Result<Self> {
+        match value {
+            mlua::Value::UserData(data) => Ok(*data.borrow::<Self>()?),
+            // Read every field directly, rather than through serde, as a table is converted on every call.
+            mlua::Value::Table(table) => Ok(Self {
+                inner: Box2 {
+                    p_x: table.get("p_x")?,
+                    p_y: table.get("p_y")?,
+                    s_x: table.get("s_x")?,
+                    s_y: table.get("s_y")?,
+                },
+            }),
+            value => Err(mlua::Error::FromLuaConversionError {
+                from: value.type_name(),
+                to: "Box2".to_string(),
+                message: None,
+            }),
+        }
+    }
+}
+
+#[export]
+impl NativeBox2 {
+    #[function(
+        from = "box2",
+        info = "Create a new native Box2.",
+        parameter(name = "p_x", info = "Point X.", kind = "number"),
+        parameter(name = "p_y", info = "Point Y.", kind = "number"),
+        parameter(name = "s_x", info = "Scale X.", kind = "number"),
+        parameter(name = "s_y", info = "Scale Y.", kind = "number"),
+        result(name = "box", info = "Box2.", kind(user_data(name = "Box2")))
+    )]
+    fn new(_: &mlua::Lua, (p_x, p_y, s_x, s_y): (f32, f32, f32, f32)) -> mlua::Result<Self> {
+        Ok(Self {
+            inner: Box2 { p_x, p_y, s_x, s_y },
+        })
+    }
+
+    #[method(
+        from = "Box2",
+        info = "Check if the box intersects another box.",
+        parameter(name = "other", info = "Other box.", kind = "Box2"),
+        result(name = "intersect", info = "True if intersecting.", kind = "boolean")
+    )]
+    fn intersect(_: &mlua::Lua, this: &Self, other: Self) -> mlua::Result<bool> {
+        Ok(unsafe { ffi::CheckCollisionRecs(this.inner.into(), other.inner.into()) })
+    }
+
+    #[method(
+        from = "Box2",
+        info = "Check if the box contains a point.",
+        parameter(name = "point", info = "Point.", kind = "Vector2"),
+        result(name = "contain", info = "True if contained.", kind = "boolean")
+    )]
+    fn contains(lua: &mlua::Lua, this: &Self, point: mlua::Value) -> mlua::Result<bool> {
+        let point = get_vector_2(lua, point)?;
+        let inner = this.inner;
+
+        // Same check as CheckCollisionPointRec: the low edge is inside the box, and the high edge is not.
+        Ok(point.x >= inner.p_x
+            && point.x < inner.p_x + inner.s_x
+            && point.y >= inner.p_y
+            && point.y < inner.p_y + inner.s_y)
+    }
+
+    #[method(
+        from = "Box2",
+        info = "Get the smallest box containing both the box and another box.",
+        parameter(name = "other", info = "Other box.", kind = "Box2"),
+        result(name = "box", info = "Box union.", kind(user_data(name = "Box2")))
+    )]
+    fn union(_: &mlua::Lua, this: &Self, other: Self) -> mlua::Result<Self> {
+        let (a, b) = (this.inner, other.inner);
+        let p_x = a.p_x.min(b.p_x);
+        let p_y = a.p_y.min(b.p_y);
+
+        Ok(Self {
+            inner: Box2 {
+                p_x,
+                p_y,
+                s_x: (a.p_x + a.s_x).max(b.p_x + b.s_x) - p_x,
+                s_y: (a.p_y + a.s_y).max(b.p_y + b.s_y) - p_y,
+            },
+        })
+    }
+
+    //================================================================
+
+    #[method(
+        from = "Box2",
+        info = "Check if two boxes are equal.",
+        operator = "eq",
+        parameter(name = "other", info = "Other box.", kind = "Box2"),
+        result(name = "equal", info = "True if equal.", kind = "boolean")
+    )]
+    fn eq(_: &mlua::Lua, this: &Self, other: Self) -> mlua::Result<bool> {
+        let (a, b) = (this.inner, other.inner);
+
+        Ok(a.p_x == b.p_x && a.p_y == b.p_y && a.s_x == b.s_x && a.s_y == b.s_y)
+    }
+
+    #[method(
+        from = "Box2",
+        info = "Get the box as a string.",
+        operator = "tostring",
+        result(name = "text", info = "Box2, as a string.", kind = "string")
+    )]
+    fn to_string(_: &mlua::Lua, this: &Self, _: ()) -> mlua::Result<String> {
+        let inner = this.inner;
+
+        Ok(format!(
+            "Box2({}, {}, {}, {})",
+            inner.p_x, inner.p_y, inner.s_x, inner.s_y
+        ))
+    }
+
+    #[method(
+        from = "Box2",
+        info = "Get a field (\"p_x\", \"p_y\", \"s_x\" or \"s_y\") of the box.",
+        operator = "index",
+        parameter(name = "key", info = "Field name.", kind = "string"),
+        result(
+            name = "value",
+            info = "Field value.",
+            kind = "number",
+            optional = true
+        )
+    )]
+    fn index(_: &mlua::Lua, this: &Self, key: String) -> mlua::Result<Option<f32>> {
+        Ok(match key.as_str() {
+            "p_x" => Some(this.inner.p_x),
+            "p_y" => Some(this.inner.p_y),
+            "s_x" => Some(this.inner.s_x),
+            "s_y" => Some(this.inner.s_y),
+            _ => None,
+        })
+    }
+
+    #[method(
+        from = "Box2",
+        info = "Set a field (\"p_x\", \"p_y\", \"s_x\" or \"s_y\") of the box.",
+        operator = "newindex",
+        parameter(name = "key", info = "Field name.", kind = "string"),
+        parameter(name = "value", info = "Field value.", kind = "number")
+    )]
+    fn new_index(_: &mlua::Lua, this: &mut Self, (key, value): (String, f32)) -> mlua::Result<()> {
+        match key.as_str() {
+            "p_x" => this.inner.p_x = value,
+            "p_y" => this.inner.p_y = value,
+            "s_x" => this.inner.s_x = value,
+            "s_y" => this.inner.s_y = value,
+            _ => {
+                return Err(mlua::Error::runtime(format!("Box2: no field \"{key}\".")));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+//================================================================
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn get_lua() -> mlua::Lua {
+        let lua = mlua::Lua::new();
+        set_global(&lua, &lua.globals()).expect("Error setting module.");
+
+        lua
+    }
+
+    #[test]
+    fn union() {
+        let lua = get_lua();
+        let union = lua
+            .load("return box2.new(0, 0, 2, 2):union(box2.new(1, -1, 4, 2))")
+            .eval::<NativeBox2>()
+            .expect("Error running code.")
+            .inner;
+
+        assert_eq!(
+            (union.p_x, union.p_y, union.s_x, union.s_y),
+            (0.0, -1.0, 5.0, 3.0)
+        );
+    }
+
+    #[test]
+    fn contains() {
+        let lua = get_lua();
+        let contain = |code: &str| -> bool {
+            lua.load(format!("return box2.new(0, 0, 2, 2):contains({code})"))
+                .eval()
+                .expect("Error running code.")
+        };
+
+        assert!(contain("{ x = 1, y = 1 }"));
+        assert!(contain("{ x = 0, y = 0 }"));
+        assert!(!contain("{ x = 2, y = 1 }"));
+        assert!(!contain("{ x = -1, y = 1 }"));
+    }
+}
@@ -1,3 +1,4 @@
+use crate::module::general::*;
 use engine_macro::*;
 
 //================================================================
@@ -5,6 +6,9 @@ use engine_macro::*;
 use chrono::prelude::*;
 use mlua::prelude::*;
 use serde_json::Value;
+use std::path::Component;
+use std::path::Path;
+use std::path::PathBuf;
 
 //================================================================
 
@@ -13,23 +17,101 @@ use serde_json::Value;
 pub fn set_global(lua: &mlua::Lua, global: &mlua::Table) -> anyhow::Result<()> {
     let data = lua.create_table()?;
 
-    data.set("get_list",    lua.create_function(self::get_list)?)?;
-    data.set("get_file",    lua.create_function(self::get_file)?)?;
-    data.set("set_file",    lua.create_function(self::set_file)?)?;
-    data.set("get_kind",    lua.create_function(self::get_kind)?)?;
-    data.set("into_string", lua.create_function(self::into_string)?)?;
-    data.set("from_string", lua.create_function(self::from_string)?)?;
-    data.set("get_system",  lua.create_function(self::get_system)?)?;
-    data.set("get_date",    lua.create_function(self::get_date)?)?;
-    data.set("get_time",    lua.create_function(self::get_time)?)?;
+    Export::set_table(lua, "data", &data)?;
 
     global.set("data", data)?;
 
+    PathKind::set_global(lua, global)?;
+    SystemKind::set_global(lua, global)?;
+
     Ok(())
 }
 
 //================================================================
 
+#[enumeration(info = "Path kind.")]
+#[derive(Clone, Copy)]
+pub enum PathKind {
+    /// File.
+    File,
+    /// Folder.
+    Folder,
+    /// Symbolic link.
+    Link,
+}
+
+#[enumeration(info = "System kind.")]
+#[derive(Clone, Copy)]
+pub enum SystemKind {
+    /// Linux.
+    Linux,
+    /// Windows.
+    Windows,
+    /// macOS.
+    Mac,
+    /// Android.
+    Android,
+    /// iOS.
+    Ios,
+    /// Any other system.
+    Other,
+}
+
+//================================================================
+
+// Root folder every data path must stay within. Only present in a safe state.
+pub struct DataRoot {
+    path: PathBuf,
+}
+
+impl DataRoot {
+    pub fn new(path: &str) -> mlua::Result<Self> {
+        Ok(Self {
+            path: Self::get_normal(Path::new(path))?,
+        })
+    }
+
+    pub fn get_path(lua: &mlua::Lua, path: &str) -> mlua::Result<PathBuf> {
+        let Some(root) = lua.app_data_ref::<Self>() else {
+            return Ok(PathBuf::from(path));
+        };
+
+        let normal = Self::get_normal(Path::new(path))?;
+
+        if normal.starts_with(&root.path) {
+            Ok(normal)
+        } else {
+            Err(mlua::Error::external(format!(
+                "Path \"{path}\" is outside of the data root \"{}\".",
+                root.path.display()
+            )))
+        }
+    }
+
+    // Lexically resolve a relative path. Absolute paths, or paths leaving the working directory, are rejected.
+    fn get_normal(path: &Path) -> mlua::Result<PathBuf> {
+        let mut normal = PathBuf::new();
+
+        for component in path.components() {
+            match component {
+                Component::Normal(name) => normal.push(name),
+                Component::CurDir => {}
+                Component::ParentDir if normal.pop() => {}
+                _ => {
+                    return Err(mlua::Error::external(format!(
+                        "Path \"{}\" is not a relative path within the working directory.",
+                        path.display()
+                    )));
+                }
+            }
+        }
+
+        Ok(normal)
+    }
+}
+
+//================================================================
+
 #[function(
     from = "data",
     info = "Get a full list of every file in a given directory.",
@@ -41,9 +123,10 @@ pub fn set_global(lua: &mlua::Lua, global: &mlua::Table) -> anyhow::Result<()> {
         kind = "table"
     )
 )]
-fn get_list(_: &mlua::Lua, (path, recurse): (String, bool)) -> mlua::Result<Vec<String>> {
+fn get_list(lua: &mlua::Lua, (path, recurse): (String, bool)) -> mlua::Result<Vec<String>> {
+    let path = DataRoot::get_path(lua, &path)?;
     let mut list = Vec::new();
-    get_list_aux(&mut list, path, recurse)?;
+    get_list_aux(&mut list, path.display().to_string(), recurse)?;
 
     Ok(list)
 }
@@ -77,10 +160,22 @@ fn get_list_aux(list: &mut Vec<String>, path: String, recurse: bool) -> anyhow::
     result(
         name = "data",
         info = "File data.",
-        kind(user_data(name = "table|string"))
+        kind(union(kind = "table", kind = "string"))
+    ),
+    overload(
+        parameter(name = "path", kind = "string"),
+        parameter(name = "binary", kind(literal(value = "true"))),
+        result(name = "data", kind = "table")
+    ),
+    overload(
+        parameter(name = "path", kind = "string"),
+        parameter(name = "binary", kind(literal(value = "false"))),
+        result(name = "data", kind = "string")
     )
 )]
 fn get_file(lua: &mlua::Lua, (path, binary): (String, bool)) -> mlua::Result<mlua::Value> {
+    let path = DataRoot::get_path(lua, &path)?;
+
     if binary {
         Ok(lua.to_value(&std::fs::read(path)?)?)
     } else {
@@ -94,7 +189,9 @@ fn get_file(lua: &mlua::Lua, (path, binary): (String, bool)) -> mlua::Result<mlu
     parameter(name = "path", info = "Path to file.", kind = "string"),
     parameter(name = "data", info = "Data to write to file.", kind = "string")
 )]
-fn set_file(_: &mlua::Lua, (path, data): (String, String)) -> mlua::Result<()> {
+fn set_file(lua: &mlua::Lua, (path, data): (String, String)) -> mlua::Result<()> {
+    let path = DataRoot::get_path(lua, &path)?;
+
     Ok(std::fs::write(path, data)?)
 }
 
@@ -109,16 +206,16 @@ fn set_file(_: &mlua::Lua, (path, data): (String, String)) -> mlua::Result<()> {
         optional = true
     )
 )]
-fn get_kind(_: &mlua::Lua, path: String) -> mlua::Result<Option<usize>> {
-    let path = std::path::Path::new(&path);
+fn get_kind(lua: &mlua::Lua, path: String) -> mlua::Result<Option<PathKind>> {
+    let path = DataRoot::get_path(lua, &path)?;
 
     if path.exists() {
         if path.is_file() {
-            return Ok(Some(0));
+            return Ok(Some(PathKind::File));
         } else if path.is_dir() {
-            return Ok(Some(1));
+            return Ok(Some(PathKind::Folder));
         } else if path.is_symlink() {
-            return Ok(Some(2));
+            return Ok(Some(PathKind::Link));
         }
     }
 
@@ -176,14 +273,15 @@ fn from_string(lua: &mlua::Lua, data: String) -> mlua::Result<mlua::Value> {
     )
 )]
 #[rustfmt::skip]
-fn get_system(_: &mlua::Lua, _: ()) -> mlua::Result<usize> {
+fn get_system(_: &mlua::Lua, _: ()) -> mlua::Result<SystemKind> {
+    // Rust names macOS "macos", not "mac".
     match std::env::consts::OS {
-        "linux"   => Ok(0),
-        "windows" => Ok(1),
-        "mac"     => Ok(2),
-        "android" => Ok(3),
-        "ios"     => Ok(4),
-        _         => Ok(5),
+        "linux"   => Ok(SystemKind::Linux),
+        "windows" => Ok(SystemKind::Windows),
+        "macos"   => Ok(SystemKind::Mac),
+        "android" => Ok(SystemKind::Android),
+        "ios"     => Ok(SystemKind::Ios),
+        _         => Ok(SystemKind::Other),
     }
 }
 
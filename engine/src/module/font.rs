@@ -1,5 +1,6 @@
 use crate::module::archive::*;
 use crate::module::general::*;
+use crate::module::vfs::*;
 use engine_macro::*;
 
 //================================================================
@@ -14,8 +15,7 @@ use raylib::prelude::*;
 pub fn set_global(lua: &mlua::Lua, global: &mlua::Table) -> anyhow::Result<()> {
     let font = lua.create_table()?;
 
-    font.set("new",         lua.create_function(self::Font::new)?)?;
-    font.set("new_archive", lua.create_function(self::Font::new_archive)?)?;
+    Export::set_table(lua, "font", &font)?;
 
     global.set("font", font)?;
 
@@ -24,11 +24,96 @@ pub fn set_global(lua: &mlua::Lua, global: &mlua::Table) -> anyhow::Result<()> {
 
 //================================================================
 
+// Draw text with a given font, or with the default font if none. Used by both Font:draw and the developer console.
+pub fn draw_text(
+    font: Option<ffi::Font>,
+    text: &str,
+    point: Vector2,
+    scale: f32,
+    space: f32,
+    color: Color,
+) -> mlua::Result<()> {
+    unsafe {
+        let font = font.unwrap_or_else(|| ffi::GetFontDefault());
+
+        ffi::DrawTextEx(
+            font,
+            c_string(text)?.as_ptr(),
+            point.into(),
+            scale,
+            space,
+            color.into(),
+        );
+
+        Ok(())
+    }
+}
+
+//================================================================
+
 #[class(info = "Font class.")]
 struct Font {
     inner: ffi::Font,
 }
 
+impl Font {
+    // Get every code-point, from a table array of every range (start and end code-point).
+    fn get_range(lua: &mlua::Lua, code_point_range: Option<mlua::Value>) -> mlua::Result<Vec<i32>> {
+        let mut range = Vec::new();
+
+        if let Some(code_point_range) = code_point_range {
+            let code_point_range: Vec<(i32, i32)> = lua.from_value(code_point_range)?;
+
+            for i in code_point_range {
+                let current_range = i.0..=i.1;
+                let current_range: Vec<i32> = current_range.collect();
+
+                range.extend(current_range);
+            }
+        };
+
+        Ok(range)
+    }
+
+    fn from_memory(
+        lua: &mlua::Lua,
+        data: &[u8],
+        extension: &str,
+        scale: i32,
+        code_point_range: Option<mlua::Value>,
+        call: &str,
+        path: &str,
+    ) -> mlua::Result<Self> {
+        unsafe {
+            let mut range = Self::get_range(lua, code_point_range)?;
+
+            let pointer = if range.is_empty() {
+                std::ptr::null_mut()
+            } else {
+                range.as_mut_ptr()
+            };
+
+            let inner = ffi::LoadFontFromMemory(
+                c_string(extension)?.as_ptr(),
+                data.as_ptr(),
+                data.len() as i32,
+                scale,
+                pointer,
+                range.len() as i32,
+            );
+
+            if ffi::IsFontValid(inner) {
+                Ok(Self { inner })
+            } else {
+                Err(mlua::Error::external(format!(
+                    "{call}(): Error loading font \"{path}\"."
+                )))
+            }
+        }
+    }
+}
+
+#[export]
 impl Font {
     #[function(
         from = "font",
@@ -37,8 +122,8 @@ impl Font {
         parameter(name = "scale", info = "Font scale.", kind = "number"),
         parameter(
             name = "range",
-            info = "Font code-point range.",
-            kind = "number",
+            info = "Font code-point range, as a table array of every range (start and end code-point).",
+            kind = "table",
             optional = true
         ),
         result(name = "font", info = "Font resource.", kind(user_data(name = "Font")))
@@ -47,19 +132,23 @@ impl Font {
         lua: &mlua::Lua,
         (path, scale, code_point_range): (String, i32, Option<mlua::Value>),
     ) -> mlua::Result<Self> {
-        unsafe {
-            let mut range = Vec::new();
-
-            if let Some(code_point_range) = code_point_range {
-                let code_point_range: Vec<(i32, i32)> = lua.from_value(code_point_range)?;
-
-                for i in code_point_range {
-                    let current_range = i.0..=i.1;
-                    let current_range: Vec<i32> = current_range.collect();
+        let file = match Vfs::get_file(lua, &path)? {
+            VfsFile::Path(file) => file,
+            VfsFile::Data(data, extension) => {
+                return Self::from_memory(
+                    lua,
+                    &data,
+                    &extension,
+                    scale,
+                    code_point_range,
+                    "font.new",
+                    &path,
+                );
+            }
+        };
 
-                    range.extend(current_range);
-                }
-            };
+        unsafe {
+            let mut range = Self::get_range(lua, code_point_range)?;
 
             let pointer = if range.is_empty() {
                 std::ptr::null_mut()
@@ -68,7 +157,7 @@ impl Font {
             };
 
             let inner = ffi::LoadFontEx(
-                c_string(&path)?.as_ptr(),
+                c_string(&file)?.as_ptr(),
                 scale,
                 pointer,
                 range.len() as i32,
@@ -96,8 +185,8 @@ impl Font {
         parameter(name = "scale", info = "Font scale.", kind = "number"),
         parameter(
             name = "range",
-            info = "Font code-point range.",
-            kind = "number",
+            info = "Font code-point range, as a table array of every range (start and end code-point).",
+            kind = "table",
             optional = true
         ),
         result(name = "font", info = "Font resource.", kind(user_data(name = "Font")))
@@ -113,43 +202,15 @@ impl Font {
     ) -> mlua::Result<Self> {
         let (data, extension) = Archive::borrow_file(&path, archive)?;
 
-        unsafe {
-            let mut range = Vec::new();
-
-            if let Some(code_point_range) = code_point_range {
-                let code_point_range: Vec<(i32, i32)> = lua.from_value(code_point_range)?;
-
-                for i in code_point_range {
-                    let current_range = i.0..=i.1;
-                    let current_range: Vec<i32> = current_range.collect();
-
-                    range.extend(current_range);
-                }
-            };
-
-            let pointer = if range.is_empty() {
-                std::ptr::null_mut()
-            } else {
-                range.as_mut_ptr()
-            };
-
-            let inner = ffi::LoadFontFromMemory(
-                c_string(&extension)?.as_ptr(),
-                data.as_ptr(),
-                data.len() as i32,
-                scale,
-                pointer,
-                range.len() as i32,
-            );
-
-            if ffi::IsFontValid(inner) {
-                Ok(Self { inner })
-            } else {
-                Err(mlua::Error::external(format!(
-                    "font.new_archive(): Error loading font \"{path}\"."
-                )))
-            }
-        }
+        Self::from_memory(
+            lua,
+            &data,
+            &extension,
+            scale,
+            code_point_range,
+            "font.new_archive",
+            &path,
+        )
     }
 
     #[method(
@@ -166,20 +227,10 @@ impl Font {
         this: &Self,
         (text, point, scale, space, color): (String, mlua::Value, f32, f32, mlua::Value),
     ) -> mlua::Result<()> {
-        unsafe {
-            let point: Vector2 = lua.from_value(point)?;
-            let color: Color = lua.from_value(color)?;
+        let point = get_vector_2(lua, point)?;
+        let color: Color = lua.from_value(color)?;
 
-            ffi::DrawTextEx(
-                this.inner,
-                c_string(&text)?.as_ptr(),
-                point.into(),
-                scale,
-                space,
-                color.into(),
-            );
-            Ok(())
-        }
+        draw_text(Some(this.inner), &text, point, scale, space, color)
     }
 
     // Original code from: https://www.raylib.com/examples/text/loader.html?name=text_rectangle_bounds
@@ -206,7 +257,7 @@ impl Font {
         this: &Self,
         (text, box_2, scale, space, color): (String, mlua::Value, f32, f32, mlua::Value),
     ) -> mlua::Result<f32> {
-        let box_2: Box2 = lua.from_value(box_2)?;
+        let box_2 = get_box_2(lua, box_2)?;
         let color: Color = lua.from_value(color)?;
 
         let length: i32 = text.len() as i32;
@@ -385,7 +436,7 @@ impl Font {
         this: &Self,
         (text, box_2, scale, space): (String, mlua::Value, f32, f32),
     ) -> mlua::Result<f32> {
-        let box_2: Box2 = lua.from_value(box_2)?;
+        let box_2 = get_box_2(lua, box_2)?;
 
         let length: i32 = text.len() as i32;
         let text = c_string(&text)?;
@@ -527,13 +578,3 @@ impl Drop for Font {
         }
     }
 }
-
-impl mlua::UserData for Font {
-    #[rustfmt::skip]
-    fn add_methods<M: mlua::UserDataMethods<Self>>(method: &mut M) {
-        method.add_method("draw",         Self::draw);
-        method.add_method("draw_wrap",    Self::draw_wrap);
-        method.add_method("measure",      Self::measure);
-        method.add_method("measure_wrap", Self::measure_wrap);
-    }
-}
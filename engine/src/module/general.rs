@@ -1,14 +1,45 @@
+use crate::module::box2::*;
+use crate::module::profile::*;
+use crate::module::vector2::*;
+use engine_macro::*;
+use mlua::prelude::*;
 use raylib::prelude::*;
 use serde::{Deserialize, Serialize};
 use std::ffi::CString;
 
 //================================================================
 
-#[derive(Serialize, Deserialize)]
+// Raylib types, given to (and taken from) Lua as a table.
+#[rustfmt::skip]
+foreign_record!(
+    name = "Vector2",
+    info = "2D vector.",
+    field(name = "x", info = "X component.", kind = "number"),
+    field(name = "y", info = "Y component.", kind = "number"),
+);
+
+#[rustfmt::skip]
+foreign_record!(
+    name = "Color",
+    info = "RGBA color, with every channel in the 0-255 range.",
+    field(name = "r", info = "Red channel.",   kind = "number"),
+    field(name = "g", info = "Green channel.", kind = "number"),
+    field(name = "b", info = "Blue channel.",  kind = "number"),
+    field(name = "a", info = "Alpha channel.", kind = "number"),
+);
+
+//================================================================
+
+#[record(info = "2D box, from its top-left point and its scale.")]
+#[derive(Clone, Copy, Serialize, Deserialize)]
 pub struct Box2 {
+    /// Point X.
     pub p_x: f32,
+    /// Point Y.
     pub p_y: f32,
+    /// Scale X.
     pub s_x: f32,
+    /// Scale Y.
     pub s_y: f32,
 }
 
@@ -20,11 +51,16 @@ impl From<Box2> for ffi::Rectangle {
 
 //================================================================
 
+#[record(info = "2D camera.")]
 #[derive(Serialize, Deserialize)]
 pub struct Camera2D {
+    /// Point of the camera on-screen, in screen space.
     pub point: Vector2,
+    /// Point to look at, in world space.
     pub shift: Vector2,
+    /// Angle, in degrees.
     pub angle: f32,
+    /// Zoom.
     pub zoom: f32,
 }
 
@@ -36,6 +72,39 @@ impl From<Camera2D> for ffi::Camera2D {
 
 //================================================================
 
+// A function exported to Lua, from the "function" attribute.
+pub struct Export {
+    pub from: &'static str,
+    pub name: &'static str,
+    pub call: fn(&mlua::Lua) -> mlua::Result<mlua::Function>,
+    // An async function yields, which it can not do from within a profile zone wrapper.
+    pub asynchronous: bool,
+}
+
+inventory::collect!(Export);
+
+impl Export {
+    // Set every function exported from a given module in a table, wrapped in a profile zone if profiling is enabled.
+    pub fn set_table(lua: &mlua::Lua, from: &str, table: &mlua::Table) -> mlua::Result<()> {
+        for export in inventory::iter::<Self> {
+            if export.from == from {
+                let call = (export.call)(lua)?;
+                let call = if export.asynchronous {
+                    call
+                } else {
+                    Profile::wrap(lua, call, export.from, export.name)?
+                };
+
+                table.set(export.name, call)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+//================================================================
+
 pub fn c_string(text: &str) -> mlua::Result<CString> {
     let convert = CString::new(text);
 
@@ -58,6 +127,86 @@ where
     }
 }
 
+// Convert a function argument, checking it against the kind given in its "parameter" meta-data.
+pub fn get_argument<T: mlua::FromLua>(
+    lua: &mlua::Lua,
+    from: &str,
+    name: &str,
+    kind: &str,
+    optional: bool,
+    value: Option<mlua::Value>,
+) -> mlua::Result<T> {
+    let value = value.unwrap_or(mlua::Value::Nil);
+
+    // A union kind (such as "table|string") is valid if any of its kinds is valid.
+    let valid = if value.is_nil() {
+        optional
+    } else {
+        kind.split('|').any(|kind| is_kind(&value, kind))
+    };
+
+    let kind_name = value.type_name();
+
+    if !valid {
+        return Err(mlua::Error::runtime(format!(
+            "{from}: parameter '{name}' expected {kind}, got {kind_name}"
+        )));
+    }
+
+    // Convert only once, keeping the conversion error (such as a missing field).
+    T::from_lua(value, lua).map_err(|error| {
+        mlua::Error::runtime(format!(
+            "{from}: parameter '{name}' expected {kind}, got {kind_name} ({error})"
+        ))
+    })
+}
+
+// Check the Lua type of a value against a kind. A record kind (such as a Vector2 table) is not converted here, as it
+// is converted on its own right after.
+fn is_kind(value: &mlua::Value, kind: &str) -> bool {
+    match (value, kind) {
+        (_, "any") => true,
+        (mlua::Value::String(_) | mlua::Value::Integer(_) | mlua::Value::Number(_), "string") => {
+            true
+        }
+        (mlua::Value::Integer(_) | mlua::Value::Number(_), "number") => true,
+        (mlua::Value::Boolean(_), "boolean") => true,
+        (mlua::Value::Function(_), "function") => true,
+        (mlua::Value::Table(_), "table" | "Vector2" | "Box2" | "Camera2D" | "Color") => true,
+        (mlua::Value::UserData(data), "Vector2") => data.is::<NativeVector2>(),
+        (mlua::Value::UserData(data), "Box2") => data.is::<NativeBox2>(),
+        (
+            _,
+            "string" | "number" | "boolean" | "function" | "table" | "Vector2" | "Box2"
+            | "Camera2D" | "Color",
+        ) => false,
+        // Every other kind (class, enumeration) is only checked on conversion.
+        _ => true,
+    }
+}
+
+// Convert a Vector2, from a table or a native Vector2.
+pub fn get_vector_2(lua: &mlua::Lua, value: mlua::Value) -> mlua::Result<Vector2> {
+    Ok(NativeVector2::from_lua(value, lua)?.inner)
+}
+
+// Convert a Box2, from a table or a native Box2.
+pub fn get_box_2(lua: &mlua::Lua, value: mlua::Value) -> mlua::Result<Box2> {
+    Ok(NativeBox2::from_lua(value, lua)?.inner)
+}
+
+// Convert a Camera2D table, in which either Vector2 may be a native Vector2.
+pub fn get_camera_2d(lua: &mlua::Lua, value: mlua::Value) -> mlua::Result<Camera2D> {
+    let table = mlua::Table::from_lua(value, lua)?;
+
+    Ok(Camera2D {
+        point: get_vector_2(lua, table.get("point")?)?,
+        shift: get_vector_2(lua, table.get("shift")?)?,
+        angle: table.get("angle")?,
+        zoom: table.get("zoom")?,
+    })
+}
+
 pub fn sub_string(_: &mlua::Lua, (value, index_a, index_b): (String, isize, Option<isize>)) -> mlua::Result<String> {
     let character: Vec<char> = value.chars().collect();
     let length = character.len() as isize;
@@ -1,3 +1,4 @@
+use crate::console::*;
 use crate::module::general::*;
 use engine_macro::*;
 
@@ -21,43 +22,15 @@ pub fn set_global(lua: &mlua::Lua, global: &mlua::Table) -> anyhow::Result<()> {
 
     //================================================================
 
-    board.set("is_press",           lua.create_function(self::board::is_press)?)?;
-    board.set("is_press_repeat",    lua.create_function(self::board::is_press_repeat)?)?;
-    board.set("is_release",         lua.create_function(self::board::is_release)?)?;
-    board.set("is_up",              lua.create_function(self::board::is_up)?)?;
-    board.set("is_down",            lua.create_function(self::board::is_down)?)?;
-    board.set("get_last_press",     lua.create_function(self::board::get_last_press)?)?;
-    board.set("get_last_character", lua.create_function(self::board::get_last_character)?)?;
-    board.set("set_clip_board",     lua.create_function(self::board::set_clip_board)?)?;
-    board.set("get_clip_board",     lua.create_function(self::board::get_clip_board)?)?;
+    Export::set_table(lua, "input.board", &board)?;
 
     //================================================================
 
-    mouse.set("is_press",       lua.create_function(self::mouse::is_press)?)?;
-    mouse.set("is_release",     lua.create_function(self::mouse::is_release)?)?;
-    mouse.set("is_up",          lua.create_function(self::mouse::is_up)?)?;
-    mouse.set("is_down",        lua.create_function(self::mouse::is_down)?)?;
-    mouse.set("is_hidden",      lua.create_function(self::mouse::is_hidden)?)?;
-    mouse.set("get_last_press", lua.create_function(self::mouse::get_last_press)?)?;
-    mouse.set("get_point",      lua.create_function(self::mouse::get_point)?)?;
-    mouse.set("get_delta",      lua.create_function(self::mouse::get_delta)?)?;
-    mouse.set("set_point",      lua.create_function(self::mouse::set_point)?)?;
-    mouse.set("get_wheel",      lua.create_function(self::mouse::get_wheel)?)?;
-    mouse.set("show_cursor",    lua.create_function(self::mouse::show_cursor)?)?;
-    mouse.set("lock_cursor",    lua.create_function(self::mouse::lock_cursor)?)?;
+    Export::set_table(lua, "input.mouse", &mouse)?;
 
     //================================================================
 
-    pad.set("is_press",       lua.create_function(self::pad::is_press)?)?;
-    pad.set("is_release",     lua.create_function(self::pad::is_release)?)?;
-    pad.set("is_up",          lua.create_function(self::pad::is_up)?)?;
-    pad.set("is_down",        lua.create_function(self::pad::is_down)?)?;
-    pad.set("is_active",      lua.create_function(self::pad::is_active)?)?;
-    pad.set("get_name",       lua.create_function(self::pad::get_name)?)?;
-    pad.set("get_last_press", lua.create_function(self::pad::get_last_press)?)?;
-    pad.set("get_axis_count", lua.create_function(self::pad::get_axis_count)?)?;
-    pad.set("get_axis_state", lua.create_function(self::pad::get_axis_state)?)?;
-    pad.set("set_vibration",  lua.create_function(self::pad::set_vibration)?)?;
+    Export::set_table(lua, "input.pad", &pad)?;
 
     //================================================================
 
@@ -66,82 +39,308 @@ pub fn set_global(lua: &mlua::Lua, global: &mlua::Table) -> anyhow::Result<()> {
     input.set("pad",   pad)?;
     global.set("input", input)?;
 
+    Key::set_global(lua, global)?;
+
     Ok(())
 }
 
 //================================================================
 
-mod board {
-    const KEY_LIST: [i32; 109] = [
-        39, 44, 45, 46, 47, 48, 49, 50, 51, 52, 53, 54, 55, 56, 57, 59, 61, 65, 66, 67, 68, 69, 70,
-        71, 72, 73, 74, 75, 76, 77, 78, 79, 80, 81, 82, 83, 84, 85, 86, 87, 88, 89, 90, 91, 92, 93,
-        96, 32, 256, 257, 258, 259, 260, 261, 262, 263, 264, 265, 266, 267, 268, 269, 280, 281,
-        282, 283, 284, 290, 291, 292, 293, 294, 295, 296, 297, 298, 299, 300, 301, 340, 341, 342,
-        343, 344, 345, 346, 347, 348, 320, 321, 322, 323, 324, 325, 326, 327, 328, 329, 330, 331,
-        332, 333, 334, 335, 336, 4, 5, 24, 25,
-    ];
+#[enumeration(name = "key", info = "Key code.")]
+#[derive(Clone, Copy)]
+pub enum Key {
+    /// Apostrophe (') key.
+    Apostrophe = 39,
+    /// Comma (,) key.
+    Comma = 44,
+    /// Minus (-) key.
+    Minus = 45,
+    /// Period (.) key.
+    Period = 46,
+    /// Slash (/) key.
+    Slash = 47,
+    /// 0 key.
+    Zero = 48,
+    /// 1 key.
+    One = 49,
+    /// 2 key.
+    Two = 50,
+    /// 3 key.
+    Three = 51,
+    /// 4 key.
+    Four = 52,
+    /// 5 key.
+    Five = 53,
+    /// 6 key.
+    Six = 54,
+    /// 7 key.
+    Seven = 55,
+    /// 8 key.
+    Eight = 56,
+    /// 9 key.
+    Nine = 57,
+    /// Semicolon (;) key.
+    Semicolon = 59,
+    /// Equal (=) key.
+    Equal = 61,
+    /// A key.
+    A = 65,
+    /// B key.
+    B = 66,
+    /// C key.
+    C = 67,
+    /// D key.
+    D = 68,
+    /// E key.
+    E = 69,
+    /// F key.
+    F = 70,
+    /// G key.
+    G = 71,
+    /// H key.
+    H = 72,
+    /// I key.
+    I = 73,
+    /// J key.
+    J = 74,
+    /// K key.
+    K = 75,
+    /// L key.
+    L = 76,
+    /// M key.
+    M = 77,
+    /// N key.
+    N = 78,
+    /// O key.
+    O = 79,
+    /// P key.
+    P = 80,
+    /// Q key.
+    Q = 81,
+    /// R key.
+    R = 82,
+    /// S key.
+    S = 83,
+    /// T key.
+    T = 84,
+    /// U key.
+    U = 85,
+    /// V key.
+    V = 86,
+    /// W key.
+    W = 87,
+    /// X key.
+    X = 88,
+    /// Y key.
+    Y = 89,
+    /// Z key.
+    Z = 90,
+    /// Left bracket ([) key.
+    LeftBracket = 91,
+    /// Backslash (\) key.
+    Backslash = 92,
+    /// Right bracket (]) key.
+    RightBracket = 93,
+    /// Grave (`) key.
+    Grave = 96,
+    /// Space key.
+    Space = 32,
+    /// Escape key.
+    Escape = 256,
+    /// Enter key.
+    Enter = 257,
+    /// Tab key.
+    Tab = 258,
+    /// Backspace key.
+    Backspace = 259,
+    /// Insert key.
+    Insert = 260,
+    /// Delete key.
+    Delete = 261,
+    /// Right arrow key.
+    Right = 262,
+    /// Left arrow key.
+    Left = 263,
+    /// Down arrow key.
+    Down = 264,
+    /// Up arrow key.
+    Up = 265,
+    /// Page up key.
+    PageUp = 266,
+    /// Page down key.
+    PageDown = 267,
+    /// Home key.
+    Home = 268,
+    /// End key.
+    End = 269,
+    /// Caps lock key.
+    CapsLock = 280,
+    /// Scroll lock key.
+    ScrollLock = 281,
+    /// Num lock key.
+    NumLock = 282,
+    /// Print screen key.
+    PrintScreen = 283,
+    /// Pause key.
+    Pause = 284,
+    /// F1 key.
+    F1 = 290,
+    /// F2 key.
+    F2 = 291,
+    /// F3 key.
+    F3 = 292,
+    /// F4 key.
+    F4 = 293,
+    /// F5 key.
+    F5 = 294,
+    /// F6 key.
+    F6 = 295,
+    /// F7 key.
+    F7 = 296,
+    /// F8 key.
+    F8 = 297,
+    /// F9 key.
+    F9 = 298,
+    /// F10 key.
+    F10 = 299,
+    /// F11 key.
+    F11 = 300,
+    /// F12 key.
+    F12 = 301,
+    /// Left shift key.
+    LeftShift = 340,
+    /// Left control key.
+    LeftControl = 341,
+    /// Left alt key.
+    LeftAlt = 342,
+    /// Left super key.
+    LeftSuper = 343,
+    /// Right shift key.
+    RightShift = 344,
+    /// Right control key.
+    RightControl = 345,
+    /// Right alt key.
+    RightAlt = 346,
+    /// Right super key.
+    RightSuper = 347,
+    /// Keyboard menu key.
+    BoardMenu = 348,
+    /// Keypad 0 key.
+    Pad0 = 320,
+    /// Keypad 1 key.
+    Pad1 = 321,
+    /// Keypad 2 key.
+    Pad2 = 322,
+    /// Keypad 3 key.
+    Pad3 = 323,
+    /// Keypad 4 key.
+    Pad4 = 324,
+    /// Keypad 5 key.
+    Pad5 = 325,
+    /// Keypad 6 key.
+    Pad6 = 326,
+    /// Keypad 7 key.
+    Pad7 = 327,
+    /// Keypad 8 key.
+    Pad8 = 328,
+    /// Keypad 9 key.
+    Pad9 = 329,
+    /// Keypad decimal (.) key.
+    PadDecimal = 330,
+    /// Keypad divide (/) key.
+    PadDivide = 331,
+    /// Keypad multiply (*) key.
+    PadMultiply = 332,
+    /// Keypad subtract (-) key.
+    PadSubtract = 333,
+    /// Keypad add (+) key.
+    PadAdd = 334,
+    /// Keypad enter key.
+    PadEnter = 335,
+    /// Keypad equal (=) key.
+    PadEqual = 336,
+    /// Android back button.
+    Back = 4,
+    /// Android menu button.
+    Menu = 5,
+    /// Android volume up button.
+    VolumeUp = 24,
+    /// Android volume down button.
+    VolumeDown = 25,
+}
 
+// Every key-board function acts as if no key is down while the developer console is open.
+mod board {
     use super::*;
 
     #[function(
         from = "input.board",
         info = "Get the state (press) of a key.",
-        parameter(name = "code", info = "Key code.", kind = "number"),
+        parameter(name = "code", info = "Key code.", kind(user_data(name = "Key"))),
         result(name = "state", info = "Key state.", kind = "boolean")
     )]
-    pub fn is_press(_: &mlua::Lua, code: i32) -> mlua::Result<bool> {
-        Ok(unsafe { ffi::IsKeyPressed(code) })
+    pub fn is_press(lua: &mlua::Lua, code: Key) -> mlua::Result<bool> {
+        Ok(!Console::is_active(lua) && unsafe { ffi::IsKeyPressed(code as i32) })
     }
 
     #[function(
         from = "input.board",
         info = "Get the state (press-repeat) of a key.",
-        parameter(name = "code", info = "Key code.", kind = "number"),
+        parameter(name = "code", info = "Key code.", kind(user_data(name = "Key"))),
         result(name = "state", info = "Key state.", kind = "boolean")
     )]
-    pub fn is_press_repeat(_: &mlua::Lua, code: i32) -> mlua::Result<bool> {
-        Ok(unsafe { ffi::IsKeyPressedRepeat(code) })
+    pub fn is_press_repeat(lua: &mlua::Lua, code: Key) -> mlua::Result<bool> {
+        Ok(!Console::is_active(lua) && unsafe { ffi::IsKeyPressedRepeat(code as i32) })
     }
 
     #[function(
         from = "input.board",
         info = "Get the state (release) of a key.",
-        parameter(name = "code", info = "Key code.", kind = "number"),
+        parameter(name = "code", info = "Key code.", kind(user_data(name = "Key"))),
         result(name = "state", info = "Key state.", kind = "boolean")
     )]
-    pub fn is_release(_: &mlua::Lua, code: i32) -> mlua::Result<bool> {
-        Ok(unsafe { ffi::IsKeyReleased(code) })
+    pub fn is_release(lua: &mlua::Lua, code: Key) -> mlua::Result<bool> {
+        Ok(!Console::is_active(lua) && unsafe { ffi::IsKeyReleased(code as i32) })
     }
 
     #[function(
         from = "input.board",
         info = "Get the state (up) of a key.",
-        parameter(name = "code", info = "Key code.", kind = "number"),
+        parameter(name = "code", info = "Key code.", kind(user_data(name = "Key"))),
         result(name = "state", info = "Key state.", kind = "boolean")
     )]
-    pub fn is_up(_: &mlua::Lua, code: i32) -> mlua::Result<bool> {
-        Ok(unsafe { ffi::IsKeyUp(code) })
+    pub fn is_up(lua: &mlua::Lua, code: Key) -> mlua::Result<bool> {
+        Ok(Console::is_active(lua) || unsafe { ffi::IsKeyUp(code as i32) })
     }
 
     #[function(
         from = "input.board",
         info = "Get the state (down) of a key.",
-        parameter(name = "code", info = "Key code.", kind = "number"),
+        parameter(name = "code", info = "Key code.", kind(user_data(name = "Key"))),
         result(name = "state", info = "Key state.", kind = "boolean")
     )]
-    pub fn is_down(_: &mlua::Lua, code: i32) -> mlua::Result<bool> {
-        Ok(unsafe { ffi::IsKeyDown(code) })
+    pub fn is_down(lua: &mlua::Lua, code: Key) -> mlua::Result<bool> {
+        Ok(!Console::is_active(lua) && unsafe { ffi::IsKeyDown(code as i32) })
     }
 
     #[function(
         from = "input.board",
         info = "Get the last key press.",
-        result(name = "code", info = "Key code.", kind = "number", optional = true)
+        result(
+            name = "code",
+            info = "Key code.",
+            kind(user_data(name = "Key")),
+            optional = true
+        )
     )]
-    pub fn get_last_press(_: &mlua::Lua, _: ()) -> mlua::Result<Option<i32>> {
+    pub fn get_last_press(lua: &mlua::Lua, _: ()) -> mlua::Result<Option<Key>> {
+        if Console::is_active(lua) {
+            return Ok(None);
+        }
+
         unsafe {
-            for x in self::KEY_LIST {
-                if ffi::IsKeyPressed(x) {
+            for x in Key::LIST {
+                if ffi::IsKeyPressed(x as i32) {
                     return Ok(Some(x));
                 }
             }
@@ -160,7 +359,12 @@ mod board {
             optional = true
         )
     )]
-    pub fn get_last_character(_: &mlua::Lua, _: ()) -> mlua::Result<Option<char>> {
+    pub fn get_last_character(lua: &mlua::Lua, _: ()) -> mlua::Result<Option<char>> {
+        // Leave every character in the queue for the console.
+        if Console::is_active(lua) {
+            return Ok(None);
+        }
+
         unsafe {
             let code = ffi::GetCharPressed();
 
@@ -300,7 +504,7 @@ mod mouse {
     )]
     pub fn set_point(lua: &mlua::Lua, point: mlua::Value) -> mlua::Result<()> {
         unsafe {
-            let point: Vector2 = lua.from_value(point)?;
+            let point = get_vector_2(lua, point)?;
             ffi::SetMousePosition(point.x as i32, point.y as i32);
             Ok(())
         }
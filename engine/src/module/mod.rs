@@ -1,11 +1,44 @@
 pub mod archive;
+pub mod box2;
 pub mod data;
 pub mod font;
 pub mod general;
 pub mod input;
 pub mod music;
 pub mod network;
+pub mod profile;
+pub mod random;
 pub mod screen;
 pub mod sound;
+pub mod task;
 pub mod texture;
+pub mod vector2;
+pub mod vfs;
 pub mod window;
+
+//================================================================
+
+// Set every module table, either every window module (which needs a window and an audio device) or every other module.
+pub fn set_global(lua: &mlua::Lua, global: &mlua::Table, window: bool) -> anyhow::Result<()> {
+    if window {
+        window::set_global(lua, global)?;
+        screen::set_global(lua, global)?;
+        texture::set_global(lua, global)?;
+        font::set_global(lua, global)?;
+        sound::set_global(lua, global)?;
+        music::set_global(lua, global)?;
+        input::set_global(lua, global)?;
+    } else {
+        data::set_global(lua, global)?;
+        archive::set_global(lua, global)?;
+        network::set_global(lua, global)?;
+        profile::set_global(lua, global)?;
+        task::set_global(lua, global)?;
+        vector2::set_global(lua, global)?;
+        box2::set_global(lua, global)?;
+        random::set_global(lua, global)?;
+        vfs::set_global(lua, global)?;
+    }
+
+    Ok(())
+}
@@ -1,4 +1,6 @@
+use crate::module::archive::*;
 use crate::module::general::*;
+use crate::module::vfs::*;
 use engine_macro::*;
 
 //================================================================
@@ -12,7 +14,7 @@ use raylib::prelude::*;
 pub fn set_global(lua: &mlua::Lua, global: &mlua::Table) -> anyhow::Result<()> {
     let music = lua.create_table()?;
 
-    music.set("new", lua.create_function(self::Music::new)?)?;
+    Export::set_table(lua, "music", &music)?;
 
     global.set("music", music)?;
 
@@ -24,8 +26,32 @@ pub fn set_global(lua: &mlua::Lua, global: &mlua::Table) -> anyhow::Result<()> {
 #[class(info = "Music class.")]
 struct Music {
     inner: ffi::Music,
+    // Data of a music loaded from memory, which is streamed from, and must outlive the music.
+    _data: Vec<u8>,
 }
 
+impl Music {
+    fn from_memory(data: Vec<u8>, extension: &str, call: &str, path: &str) -> mlua::Result<Self> {
+        unsafe {
+            let inner = ffi::LoadMusicStreamFromMemory(
+                c_string(extension)?.as_ptr(),
+                data.as_ptr(),
+                data.len() as i32,
+            );
+
+            if ffi::IsMusicValid(inner) {
+                // Moving the vector does not move its heap buffer, so the pointer given to the stream stays valid.
+                Ok(Self { inner, _data: data })
+            } else {
+                Err(mlua::Error::external(format!(
+                    "{call}(): Error loading music \"{path}\"."
+                )))
+            }
+        }
+    }
+}
+
+#[export]
 impl Music {
     #[function(
         from = "music",
@@ -37,12 +63,22 @@ impl Music {
             kind(user_data(name = "Music"))
         )
     )]
-    fn new(_: &mlua::Lua, path: String) -> mlua::Result<Self> {
+    fn new(lua: &mlua::Lua, path: String) -> mlua::Result<Self> {
+        let file = match Vfs::get_file(lua, &path)? {
+            VfsFile::Path(file) => file,
+            VfsFile::Data(data, extension) => {
+                return Self::from_memory(data, &extension, "music.new", &path);
+            }
+        };
+
         unsafe {
-            let inner = ffi::LoadMusicStream(c_string(&path)?.as_ptr());
+            let inner = ffi::LoadMusicStream(c_string(&file)?.as_ptr());
 
             if ffi::IsMusicValid(inner) {
-                Ok(Self { inner })
+                Ok(Self {
+                    inner,
+                    _data: Vec::new(),
+                })
             } else {
                 Err(mlua::Error::external(format!(
                     "music.new(): Error loading music \"{path}\"."
@@ -51,6 +87,30 @@ impl Music {
         }
     }
 
+    #[function(
+        from = "music",
+        info = "Create a new Music resource from an archive. The file is kept in memory, and streamed from it.",
+        parameter(name = "path", info = "Path to music.", kind = "string"),
+        parameter(
+            name = "archive",
+            info = "Archive to load the asset from.",
+            kind(user_data(name = "Archive"))
+        ),
+        result(
+            name = "music",
+            info = "Music resource.",
+            kind(user_data(name = "Music"))
+        )
+    )]
+    fn new_archive(
+        _: &mlua::Lua,
+        (path, archive): (String, mlua::AnyUserData),
+    ) -> mlua::Result<Self> {
+        let (data, extension) = Archive::borrow_file(&path, archive)?;
+
+        Self::from_memory(data, &extension, "music.new_archive", &path)
+    }
+
     #[method(from = "Music", info = "Update music.")]
     fn update(_: &mlua::Lua, this: &Self, _: ()) -> mlua::Result<()> {
         unsafe {
@@ -174,21 +234,3 @@ impl Drop for Music {
         }
     }
 }
-
-impl mlua::UserData for Music {
-    #[rustfmt::skip]
-    fn add_methods<M: mlua::UserDataMethods<Self>>(method: &mut M) {
-        method.add_method("update",     Self::update);
-        method.add_method("play",       Self::play);
-        method.add_method("stop",       Self::stop);
-        method.add_method("pause",      Self::pause);
-        method.add_method("resume",     Self::resume);
-        method.add_method("is_play",    Self::is_play);
-        method.add_method("get_length", Self::get_length);
-        method.add_method("get_time",   Self::get_time);
-        method.add_method("set_time",   Self::set_time);
-        method.add_method("set_volume", Self::set_volume);
-        method.add_method("set_pitch",  Self::set_pitch);
-        method.add_method("set_pan",    Self::set_pan);
-    }
-}
@@ -28,22 +28,37 @@ use std::time::SystemTime;
 pub fn set_global(lua: &mlua::Lua, global: &mlua::Table) -> anyhow::Result<()> {
     let network = lua.create_table()?;
 
-    network.set("new_server", lua.create_function(self::Server::new_server)?)?;
-    network.set("new_client", lua.create_function(self::Client::new_client)?)?;
+    Export::set_table(lua, "network", &network)?;
 
     global.set("network", network)?;
 
+    ConnectionStatus::set_global(lua, global)?;
+
     Ok(())
 }
 
 //================================================================
 
+#[enumeration(info = "Connection status.")]
+#[derive(Clone, Copy)]
+pub enum ConnectionStatus {
+    /// Connected to the server.
+    Connected,
+    /// Connecting to the server.
+    Connecting,
+    /// Disconnected from the server.
+    Disconnected,
+}
+
+//================================================================
+
 #[class(info = "Server class.")]
 struct Server {
     server: RenetServer,
     transport: NetcodeServerTransport,
 }
 
+#[export]
 impl Server {
     #[function(
         from = "network",
@@ -225,7 +240,11 @@ impl Server {
         Ok(())
     }
 
-    #[method(from = "Server", info = "Disconnect a specific client.")]
+    #[method(
+        from = "Server",
+        info = "Disconnect a specific client.",
+        parameter(name = "client", info = "Specific client.", kind = "number")
+    )]
     fn disconnect(_: &mlua::Lua, this: &mut Self, client: u64) -> mlua::Result<()> {
         this.server.disconnect(client);
 
@@ -239,7 +258,17 @@ impl Server {
         Ok(())
     }
 
-    #[method(from = "Server", info = "Get the user-data for a specific client.")]
+    #[method(
+        from = "Server",
+        info = "Get the user-data for a specific client.",
+        parameter(name = "client", info = "Specific client.", kind = "number"),
+        result(
+            name = "user_data",
+            info = "Client user-data.",
+            kind = "any",
+            optional = true
+        )
+    )]
     fn get_client_user_data(
         lua: &mlua::Lua,
         this: &mut Self,
@@ -258,19 +287,6 @@ impl Server {
     }
 }
 
-#[rustfmt::skip]
-impl mlua::UserData for Server {
-    fn add_methods<M: mlua::UserDataMethods<Self>>(method: &mut M) {
-        method.add_method_mut("update",               Self::update);
-        method.add_method_mut("set",                  Self::set);
-        method.add_method_mut("set_client",           Self::set_client);
-        method.add_method_mut("set_client_except",    Self::set_client_except);
-        method.add_method_mut("disconnect",           Self::disconnect);
-        method.add_method_mut("disconnect_all",       Self::disconnect_all);
-        method.add_method_mut("get_client_user_data", Self::get_client_user_data);
-    }
-}
-
 impl Drop for Server {
     fn drop(&mut self) {
         self.transport.disconnect_all(&mut self.server);
@@ -285,6 +301,7 @@ struct Client {
     transport: NetcodeClientTransport,
 }
 
+#[export]
 impl Client {
     #[function(
         from = "network",
@@ -310,7 +327,12 @@ impl Client {
             kind = "number"
         ),
         parameter(name = "port", info = "Address port.", kind = "number"),
-        parameter(name = "user_data", info = "User data.", kind = "table"),
+        parameter(
+            name = "user_data",
+            info = "User data.",
+            kind = "table",
+            optional = true
+        ),
         result(
             name = "client",
             info = "Client resource.",
@@ -428,14 +450,14 @@ impl Client {
             kind(user_data(name = "ConnectionStatus"))
         )
     )]
-    fn get_status(_: &mlua::Lua, this: &mut Self, _: ()) -> mlua::Result<usize> {
+    fn get_status(_: &mlua::Lua, this: &mut Self, _: ()) -> mlua::Result<ConnectionStatus> {
         if this.client.is_connected() {
-            return Ok(0);
+            return Ok(ConnectionStatus::Connected);
         } else if this.client.is_connecting() {
-            return Ok(1);
+            return Ok(ConnectionStatus::Connecting);
         }
 
-        Ok(2)
+        Ok(ConnectionStatus::Disconnected)
     }
 
     #[method(
@@ -448,17 +470,6 @@ impl Client {
     }
 }
 
-#[rustfmt::skip]
-impl mlua::UserData for Client {
-    fn add_methods<M: mlua::UserDataMethods<Self>>(method: &mut M) {
-        method.add_method_mut("update",              Self::update);
-        method.add_method_mut("set",                 Self::set);
-        method.add_method_mut("disconnect",          Self::disconnect);
-        method.add_method_mut("get_status",          Self::get_status);
-        method.add_method_mut("get_identifier",      Self::get_identifier);
-    }
-}
-
 impl Drop for Client {
     fn drop(&mut self) {
         self.transport.disconnect();
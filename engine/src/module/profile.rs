@@ -0,0 +1,331 @@
+use crate::module::data::*;
+use crate::module::general::*;
+use engine_macro::*;
+
+//================================================================
+
+use raylib::prelude::*;
+use serde_json::json;
+use std::collections::HashMap;
+use std::collections::VecDeque;
+use std::rc::Rc;
+use std::time::Instant;
+
+//================================================================
+
+#[rustfmt::skip]
+#[module(name = "profile", info = "Profile API. Zones are only recorded if profiling is enabled in the \"info\" table.")]
+pub fn set_global(lua: &mlua::Lua, global: &mlua::Table) -> anyhow::Result<()> {
+    let profile = lua.create_table()?;
+
+    Export::set_table(lua, "profile", &profile)?;
+
+    global.set("profile", profile)?;
+
+    Ok(())
+}
+
+//================================================================
+
+struct ProfileEvent {
+    name: Rc<str>,
+    // Time (in micro-seconds) since the profiler was created.
+    time: u64,
+    // Duration (in micro-seconds).
+    span: u64,
+}
+
+pub struct Profile {
+    time: Instant,
+    // Open zone list.
+    open: Vec<(Rc<str>, Instant)>,
+    // Closed zone list, kept for export.
+    event: VecDeque<ProfileEvent>,
+    // Time (in micro-seconds) of the current frame's start.
+    frame_time: u64,
+    // Time (in milli-seconds) of every recent frame.
+    frame_list: VecDeque<f32>,
+    // Total time (in milli-seconds) of every zone in the last frame.
+    frame_zone: Vec<(Rc<str>, f32)>,
+    graph: bool,
+}
+
+impl Profile {
+    const EVENT_LIMIT: usize = 262144;
+    const FRAME_LIMIT: usize = 240;
+    const GRAPH_ZONE: usize = 8;
+    const GRAPH_SCALE_Y: i32 = 96;
+    // Frame time (in milli-seconds) at the top of the graph.
+    const GRAPH_LIMIT: f32 = 1000.0 / 30.0;
+    const GRAPH_TARGET: f32 = 1000.0 / 60.0;
+    const TEXT_SIZE: i32 = 10;
+
+    pub fn new() -> Self {
+        Self {
+            time: Instant::now(),
+            open: Vec::new(),
+            event: VecDeque::new(),
+            frame_time: 0,
+            frame_list: VecDeque::new(),
+            frame_zone: Vec::new(),
+            graph: false,
+        }
+    }
+
+    // Open a zone. Every zone must be closed with "close", in reverse order.
+    pub fn begin(lua: &mlua::Lua, name: Rc<str>) {
+        if let Some(mut profile) = lua.app_data_mut::<Self>() {
+            profile.open.push((name, Instant::now()));
+        }
+    }
+
+    pub fn close(lua: &mlua::Lua) {
+        if let Some(mut profile) = lua.app_data_mut::<Self>()
+            && let Some((name, time)) = profile.open.pop()
+        {
+            let event = ProfileEvent {
+                name,
+                time: time.duration_since(profile.time).as_micros() as u64,
+                span: time.elapsed().as_micros() as u64,
+            };
+
+            if profile.event.len() >= Self::EVENT_LIMIT {
+                profile.event.pop_front();
+            }
+
+            profile.event.push_back(event);
+        }
+    }
+
+    // Mark a frame boundary.
+    pub fn frame(lua: &mlua::Lua) {
+        if let Some(mut profile) = lua.app_data_mut::<Self>() {
+            let time = profile.time.elapsed().as_micros() as u64;
+            let span = (time - profile.frame_time) as f32 / 1000.0;
+            let mut zone: HashMap<Rc<str>, f32> = HashMap::new();
+
+            // Zones are stored in closing order, so stop at the first one closed before this frame.
+            for event in profile.event.iter().rev() {
+                if event.time + event.span < profile.frame_time {
+                    break;
+                }
+
+                *zone.entry(event.name.clone()).or_default() += event.span as f32 / 1000.0;
+            }
+
+            let mut zone: Vec<(Rc<str>, f32)> = zone.into_iter().collect();
+            zone.sort_by(|a, b| b.1.total_cmp(&a.1));
+            zone.truncate(Self::GRAPH_ZONE);
+
+            if profile.frame_list.len() >= Self::FRAME_LIMIT {
+                profile.frame_list.pop_front();
+            }
+
+            profile.frame_list.push_back(span);
+            profile.frame_zone = zone;
+            profile.frame_time = time;
+        }
+    }
+
+    // Draw the frame graph. Must be called within a draw session.
+    pub fn draw(lua: &mlua::Lua) -> mlua::Result<()> {
+        let Some(profile) = lua.app_data_ref::<Self>() else {
+            return Ok(());
+        };
+
+        if !profile.graph {
+            return Ok(());
+        }
+
+        unsafe {
+            let scale_x = Self::FRAME_LIMIT as i32;
+            let scale_y = Self::GRAPH_SCALE_Y;
+            let point_y = ffi::GetScreenHeight() - scale_y;
+
+            ffi::DrawRectangle(
+                0,
+                point_y,
+                scale_x,
+                scale_y,
+                Color::new(0, 0, 0, 192).into(),
+            );
+
+            for (i, span) in profile.frame_list.iter().enumerate() {
+                let color = if *span > Self::GRAPH_TARGET {
+                    Color::RED
+                } else {
+                    Color::GREEN
+                };
+                let y = ((span / Self::GRAPH_LIMIT).min(1.0) * scale_y as f32) as i32;
+
+                ffi::DrawRectangle(i as i32, point_y + scale_y - y, 1, y, color.into());
+            }
+
+            let target = point_y + scale_y
+                - (Self::GRAPH_TARGET / Self::GRAPH_LIMIT * scale_y as f32) as i32;
+            ffi::DrawRectangle(0, target, scale_x, 1, Color::WHITE.into());
+
+            let mut text_list = vec![format!(
+                "Frame: {:.2} ms",
+                profile.frame_list.back().copied().unwrap_or_default()
+            )];
+
+            for (name, span) in &profile.frame_zone {
+                text_list.push(format!("{name}: {span:.2} ms"));
+            }
+
+            for (i, text) in text_list.iter().enumerate() {
+                let text = c_string(text)?;
+
+                ffi::DrawText(
+                    text.as_ptr(),
+                    scale_x + 4,
+                    point_y + i as i32 * (Self::TEXT_SIZE + 2),
+                    Self::TEXT_SIZE,
+                    Color::WHITE.into(),
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    // Wrap an engine function in a zone, if profiling is enabled. Called for every exported function, as it is set
+    // in its module table.
+    pub fn wrap(
+        lua: &mlua::Lua,
+        call: mlua::Function,
+        from: &str,
+        name: &str,
+    ) -> mlua::Result<mlua::Function> {
+        // A zone around "begin" or "close" would close the zone opened by the caller. A zone around "screen.draw"
+        // would span the frame boundary, so it opens its own zone instead.
+        if lua.app_data_ref::<Self>().is_none()
+            || (from == "profile" && matches!(name, "begin" | "close"))
+            || (from == "screen" && name == "draw")
+        {
+            return Ok(call);
+        }
+
+        let zone: Rc<str> = Rc::from(format!("{from}.{name}"));
+
+        lua.create_function(move |lua, argument: mlua::MultiValue| {
+            Self::begin(lua, zone.clone());
+            let result = call.call::<mlua::MultiValue>(argument);
+            Self::close(lua);
+
+            result
+        })
+    }
+
+    // Run an engine method in a zone, if profiling is enabled. Called for every exported method, as a method is not
+    // set in a module table.
+    pub fn scope<R>(lua: &mlua::Lua, name: &str, call: impl FnOnce() -> R) -> R {
+        if lua.app_data_ref::<Self>().is_none() {
+            return call();
+        }
+
+        Self::begin(lua, Rc::from(name));
+        let result = call();
+        Self::close(lua);
+
+        result
+    }
+}
+
+//================================================================
+
+#[function(
+    from = "profile",
+    info = "Open a profile zone. Every zone must be closed with `close`.",
+    parameter(name = "name", info = "Zone name.", kind = "string")
+)]
+fn begin(lua: &mlua::Lua, name: String) -> mlua::Result<()> {
+    Profile::begin(lua, Rc::from(name));
+
+    Ok(())
+}
+
+#[function(from = "profile", info = "Close the last opened profile zone.")]
+fn close(lua: &mlua::Lua, _: ()) -> mlua::Result<()> {
+    Profile::close(lua);
+
+    Ok(())
+}
+
+#[function(
+    from = "profile",
+    info = "Call a function within a profile zone.",
+    generic = "T",
+    parameter(name = "name", info = "Zone name.", kind = "string"),
+    parameter(
+        name = "call",
+        info = "Zone function.",
+        kind(call(result(generic(name = "T"))))
+    ),
+    result(
+        name = "...",
+        info = "Every result of the zone function.",
+        kind(generic(name = "T"))
+    )
+)]
+fn zone(lua: &mlua::Lua, (name, call): (String, mlua::Function)) -> mlua::Result<mlua::MultiValue> {
+    Profile::begin(lua, Rc::from(name));
+    let result = call.call::<mlua::MultiValue>(());
+    Profile::close(lua);
+
+    result
+}
+
+#[function(
+    from = "profile",
+    info = "Show or hide the on-screen frame graph.",
+    parameter(name = "show", info = "Show the graph.", kind = "boolean")
+)]
+fn set_graph(lua: &mlua::Lua, show: bool) -> mlua::Result<()> {
+    if let Some(mut profile) = lua.app_data_mut::<Profile>() {
+        profile.graph = show;
+    }
+
+    Ok(())
+}
+
+#[function(
+    from = "profile",
+    info = "Export every recorded zone to a file, in the Chrome trace format.",
+    parameter(name = "path", info = "Path to file.", kind = "string")
+)]
+fn export(lua: &mlua::Lua, path: String) -> mlua::Result<()> {
+    let path = DataRoot::get_path(lua, &path)?;
+
+    let Some(profile) = lua.app_data_ref::<Profile>() else {
+        return Err(mlua::Error::external(
+            "Profiling is not enabled in the \"info\" table.",
+        ));
+    };
+
+    let event: Vec<serde_json::Value> = profile
+        .event
+        .iter()
+        .map(|event| {
+            json!({
+                "name": &*event.name,
+                "cat": "flak",
+                "ph": "X",
+                "ts": event.time,
+                "dur": event.span,
+                "pid": 1,
+                "tid": 1,
+            })
+        })
+        .collect();
+
+    let data = json!({
+        "traceEvents": event,
+        "displayTimeUnit": "ms",
+    });
+
+    std::fs::write(path, data.to_string())?;
+
+    Ok(())
+}
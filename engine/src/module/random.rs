@@ -0,0 +1,417 @@
+use crate::module::general::*;
+use crate::module::vector2::*;
+use engine_macro::*;
+
+//================================================================
+
+use mlua::prelude::*;
+use raylib::prelude::*;
+use serde::{Deserialize, Serialize};
+
+//================================================================
+
+#[rustfmt::skip]
+#[module(name = "random", info = "Random API.")]
+pub fn set_global(lua: &mlua::Lua, global: &mlua::Table) -> anyhow::Result<()> {
+    let random = lua.create_table()?;
+
+    Export::set_table(lua, "random", &random)?;
+
+    global.set("random", random)?;
+
+    Ok(())
+}
+
+//================================================================
+
+#[class(
+    info = "Random number generator. Every generator is an independent stream, giving the same sequence on every platform for the same seed. Can be serialized with data.into_string, and restored with random.from_state."
+)]
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Random {
+    // xoshiro128** state. Every value is a 32-bit integer, as a Lua number can not hold every 64-bit integer.
+    state: [u32; 4],
+}
+
+impl Random {
+    // Number of term to use for the logarithm series.
+    const LOG_TERM: usize = 16;
+
+    fn from_seed(seed: u64) -> Self {
+        // Expand the seed with SplitMix64, as xoshiro128** must not start from a zero state.
+        let mut seed = seed;
+        let mut next = || {
+            seed = seed.wrapping_add(0x9E3779B97F4A7C15);
+            let mut value = seed;
+            value = (value ^ (value >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+            value = (value ^ (value >> 27)).wrapping_mul(0x94D049BB133111EB);
+            value ^ (value >> 31)
+        };
+        let (a, b) = (next(), next());
+
+        Self {
+            state: [a as u32, (a >> 32) as u32, b as u32, (b >> 32) as u32],
+        }
+    }
+
+    fn get_u32(&mut self) -> u32 {
+        let state = &mut self.state;
+        let value = state[1].wrapping_mul(5).rotate_left(7).wrapping_mul(9);
+        let shift = state[1] << 9;
+
+        state[2] ^= state[0];
+        state[3] ^= state[1];
+        state[1] ^= state[2];
+        state[0] ^= state[3];
+        state[2] ^= shift;
+        state[3] = state[3].rotate_left(11);
+
+        value
+    }
+
+    fn get_u64(&mut self) -> u64 {
+        ((self.get_u32() as u64) << 32) | self.get_u32() as u64
+    }
+
+    // Get a number in the [0.0, 1.0) range, using 53 bits.
+    fn get_uniform(&mut self) -> f64 {
+        (self.get_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    // Natural logarithm of a positive number. Only the basic arithmetic operations are used, as the result of
+    // f64::ln may differ from platform to platform.
+    fn get_log(value: f64) -> f64 {
+        let bits = value.to_bits();
+        let mut exponent = ((bits >> 52) & 0x7FF) as i64 - 1023;
+        let mut mantissa = f64::from_bits((bits & 0x000F_FFFF_FFFF_FFFF) | (1023 << 52));
+
+        // Keep the mantissa near 1.0, for the series to converge faster.
+        if mantissa > std::f64::consts::SQRT_2 {
+            mantissa /= 2.0;
+            exponent += 1;
+        }
+
+        // ln(m) = 2 * atanh((m - 1) / (m + 1)).
+        let z = (mantissa - 1.0) / (mantissa + 1.0);
+        let z_2 = z * z;
+        let mut term = z;
+        let mut sum = 0.0;
+
+        for i in 0..Self::LOG_TERM {
+            sum += term / (2 * i + 1) as f64;
+            term *= z_2;
+        }
+
+        exponent as f64 * std::f64::consts::LN_2 + 2.0 * sum
+    }
+}
+
+#[export]
+impl Random {
+    #[function(
+        from = "random",
+        info = "Create a new random number generator.",
+        parameter(
+            name = "seed",
+            info = "Seed. If missing, the current time will be used instead.",
+            kind = "number",
+            optional = true
+        ),
+        result(
+            name = "random",
+            info = "Random number generator.",
+            kind(user_data(name = "Random"))
+        )
+    )]
+    fn new(lua: &mlua::Lua, seed: Option<i64>) -> mlua::Result<mlua::AnyUserData> {
+        let seed = match seed {
+            Some(seed) => seed as u64,
+            None => std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map_err(mlua::Error::external)?
+                .as_nanos() as u64,
+        };
+
+        // Use a serializable user-data, for data.into_string.
+        lua.create_ser_userdata(Self::from_seed(seed))
+    }
+
+    #[function(
+        from = "random",
+        info = "Restore a random number generator, from its state (a random number generator serialized with data.into_string, and deserialized with data.from_string). The generator will continue from where it was serialized.",
+        parameter(
+            name = "state",
+            info = "Random number generator state.",
+            kind = "table"
+        ),
+        result(
+            name = "random",
+            info = "Random number generator.",
+            kind(user_data(name = "Random"))
+        )
+    )]
+    fn from_state(lua: &mlua::Lua, state: mlua::Value) -> mlua::Result<mlua::AnyUserData> {
+        let random: Self = lua.from_value(state)?;
+
+        if random.state == [0; 4] {
+            return Err(mlua::Error::runtime(
+                "random.from_state(): State can not be zero.",
+            ));
+        }
+
+        lua.create_ser_userdata(random)
+    }
+
+    //================================================================
+
+    #[method(
+        from = "Random",
+        info = "Get a number in the [0.0, 1.0) range.",
+        result(name = "value", info = "Random number.", kind = "number")
+    )]
+    fn uniform(_: &mlua::Lua, this: &mut Self, _: ()) -> mlua::Result<f64> {
+        Ok(this.get_uniform())
+    }
+
+    #[method(
+        from = "Random",
+        info = "Get an integer in the [minimum, maximum] range.",
+        parameter(name = "minimum", info = "Minimum value.", kind = "number"),
+        parameter(name = "maximum", info = "Maximum value.", kind = "number"),
+        result(name = "value", info = "Random integer.", kind = "number")
+    )]
+    fn int(_: &mlua::Lua, this: &mut Self, (minimum, maximum): (i64, i64)) -> mlua::Result<i64> {
+        if minimum > maximum {
+            return Err(mlua::Error::runtime(
+                "Random:int(): Minimum is greater than maximum.",
+            ));
+        }
+
+        let span = (maximum.wrapping_sub(minimum) as u64).wrapping_add(1);
+
+        // The span can only be zero if it covers every 64-bit integer.
+        if span == 0 {
+            return Ok(this.get_u64() as i64);
+        }
+
+        // Reject every value past the last whole multiple of the span, to not favor the lower values.
+        let limit = u64::MAX - u64::MAX % span;
+
+        loop {
+            let value = this.get_u64();
+
+            if value < limit {
+                return Ok(minimum.wrapping_add((value % span) as i64));
+            }
+        }
+    }
+
+    #[method(
+        from = "Random",
+        info = "Get a number in the [minimum, maximum) range.",
+        parameter(name = "minimum", info = "Minimum value.", kind = "number"),
+        parameter(name = "maximum", info = "Maximum value.", kind = "number"),
+        result(name = "value", info = "Random number.", kind = "number")
+    )]
+    fn range(_: &mlua::Lua, this: &mut Self, (minimum, maximum): (f64, f64)) -> mlua::Result<f64> {
+        Ok(minimum + (maximum - minimum) * this.get_uniform())
+    }
+
+    #[method(
+        from = "Random",
+        info = "Get a number from a normal distribution.",
+        parameter(
+            name = "mean",
+            info = "Mean. Default: 0.0.",
+            kind = "number",
+            optional = true
+        ),
+        parameter(
+            name = "deviation",
+            info = "Standard deviation. Default: 1.0.",
+            kind = "number",
+            optional = true
+        ),
+        result(name = "value", info = "Random number.", kind = "number")
+    )]
+    fn gaussian(
+        _: &mlua::Lua,
+        this: &mut Self,
+        (mean, deviation): (Option<f64>, Option<f64>),
+    ) -> mlua::Result<f64> {
+        // Marsaglia polar method. The second value is discarded, to keep the state as only the generator state.
+        loop {
+            let x = this.get_uniform() * 2.0 - 1.0;
+            let y = this.get_uniform() * 2.0 - 1.0;
+            let s = x * x + y * y;
+
+            if s > 0.0 && s < 1.0 {
+                let value = x * (-2.0 * Self::get_log(s) / s).sqrt();
+
+                return Ok(mean.unwrap_or(0.0) + value * deviation.unwrap_or(1.0));
+            }
+        }
+    }
+
+    #[method(
+        from = "Random",
+        info = "Get a random value from a table array.",
+        parameter(name = "list", info = "Table array to pick from.", kind = "table"),
+        parameter(
+            name = "weight",
+            info = "Table array with the weight of every value in the list. If missing, every value is equally likely.",
+            kind = "table",
+            optional = true
+        ),
+        result(name = "value", info = "Random value.", kind = "any")
+    )]
+    fn choice(
+        _: &mlua::Lua,
+        this: &mut Self,
+        (list, weight): (Vec<mlua::Value>, Option<Vec<f64>>),
+    ) -> mlua::Result<mlua::Value> {
+        if list.is_empty() {
+            return Err(mlua::Error::runtime("Random:choice(): List is empty."));
+        }
+
+        let Some(weight) = weight else {
+            let index = (this.get_uniform() * list.len() as f64) as usize;
+            return Ok(list[index.min(list.len() - 1)].clone());
+        };
+
+        if weight.len() != list.len() {
+            return Err(mlua::Error::runtime(
+                "Random:choice(): Weight count does not match list count.",
+            ));
+        }
+
+        if weight.iter().any(|value| value.is_nan() || *value < 0.0) {
+            return Err(mlua::Error::runtime(
+                "Random:choice(): Weight can not be negative.",
+            ));
+        }
+
+        let total: f64 = weight.iter().sum();
+
+        if total <= 0.0 {
+            return Err(mlua::Error::runtime(
+                "Random:choice(): Weight total must be greater than zero.",
+            ));
+        }
+
+        let mut value = this.get_uniform() * total;
+
+        for (index, weight) in weight.iter().enumerate() {
+            if value < *weight {
+                return Ok(list[index].clone());
+            }
+
+            value -= weight;
+        }
+
+        // Floating-point error may skip past the last value with a weight.
+        let index = weight.iter().rposition(|value| *value > 0.0).unwrap_or(0);
+
+        Ok(list[index].clone())
+    }
+
+    #[method(
+        from = "Random",
+        info = "Shuffle a table array, in place.",
+        parameter(name = "list", info = "Table array to shuffle.", kind = "table"),
+        result(name = "list", info = "Shuffled table array.", kind = "table")
+    )]
+    fn shuffle(_: &mlua::Lua, this: &mut Self, list: mlua::Table) -> mlua::Result<mlua::Table> {
+        // Fisher-Yates shuffle.
+        for i in (2..=list.raw_len()).rev() {
+            let j = (this.get_uniform() * i as f64) as usize + 1;
+            let a: mlua::Value = list.raw_get(i)?;
+            let b: mlua::Value = list.raw_get(j)?;
+
+            list.raw_set(i, b)?;
+            list.raw_set(j, a)?;
+        }
+
+        Ok(list)
+    }
+
+    #[method(
+        from = "Random",
+        info = "Get a random point within a circle, centered on the origin.",
+        parameter(
+            name = "radius",
+            info = "Circle radius. Default: 1.0.",
+            kind = "number",
+            optional = true
+        ),
+        result(
+            name = "point",
+            info = "Random point.",
+            kind(user_data(name = "Vector2"))
+        )
+    )]
+    fn point_in_circle(
+        _: &mlua::Lua,
+        this: &mut Self,
+        radius: Option<f32>,
+    ) -> mlua::Result<NativeVector2> {
+        let radius = radius.unwrap_or(1.0);
+
+        // Reject every point outside of the circle, rather than use sin/cos, which may differ from platform to platform.
+        loop {
+            let x = this.get_uniform() * 2.0 - 1.0;
+            let y = this.get_uniform() * 2.0 - 1.0;
+
+            if x * x + y * y <= 1.0 {
+                return Ok(NativeVector2 {
+                    inner: Vector2::new(x as f32 * radius, y as f32 * radius),
+                });
+            }
+        }
+    }
+}
+
+//================================================================
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // Every value is fixed, as the same seed must give the same sequence on every platform (and in every version).
+    #[test]
+    fn from_seed() {
+        assert_eq!(
+            Random::from_seed(1).state,
+            [0x89025CC1, 0x910A2DEC, 0x658EEC67, 0xBEEB8DA1]
+        );
+    }
+
+    #[test]
+    fn get_u32() {
+        let mut random = Random::from_seed(1);
+        let list: Vec<u32> = (0..4).map(|_| random.get_u32()).collect();
+
+        assert_eq!(list, [0x650941BA, 0x54D30301, 0x25D2F321, 0x3FABDCA9]);
+    }
+
+    #[test]
+    fn get_uniform() {
+        let mut random = Random::from_seed(1);
+        let list: Vec<f64> = (0..3).map(|_| random.get_uniform()).collect();
+
+        assert_eq!(
+            list,
+            [0.3946724967871198, 0.14775008737312534, 0.16688350750476744]
+        );
+    }
+
+    #[test]
+    fn get_log() {
+        assert_eq!(Random::get_log(1.0), 0.0);
+        assert_eq!(Random::get_log(2.0), std::f64::consts::LN_2);
+        assert_eq!(Random::get_log(0.5), -std::f64::consts::LN_2);
+        assert_eq!(Random::get_log(10.0), 2.3025850929940455);
+        assert_eq!(Random::get_log(1e-10), -23.025850929940454);
+        assert_eq!(Random::get_log(123456.789), 11.723646487185881);
+    }
+}
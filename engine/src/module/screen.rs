@@ -1,5 +1,11 @@
-use super::general::Box2;
-use super::general::Camera2D;
+use super::general::Export;
+use super::general::get_box_2;
+use super::general::get_camera_2d;
+use super::general::get_vector_2;
+use crate::console::*;
+use crate::module::profile::*;
+use crate::module::task::*;
+use crate::reload::*;
 use engine_macro::*;
 
 //================================================================
@@ -14,18 +20,7 @@ use raylib::prelude::*;
 pub fn set_global(lua: &mlua::Lua, global: &mlua::Table) -> anyhow::Result<()> {
     let screen = lua.create_table()?;
 
-    screen.set("wipe",                lua.create_function(self::wipe)?)?;
-    screen.set("draw",                lua.create_function(self::draw)?)?;
-    screen.set("draw_2D",             lua.create_function(self::draw_2D)?)?;
-    screen.set("draw_2D_begin",             lua.create_function(self::draw_2D_begin)?)?;
-    screen.set("draw_2D_close",             lua.create_function(self::draw_2D_close)?)?;
-    screen.set("draw_scissor",        lua.create_function(self::draw_scissor)?)?;
-    screen.set("draw_scissor_begin",  lua.create_function(self::draw_scissor_begin)?)?;
-    screen.set("draw_scissor_close",  lua.create_function(self::draw_scissor_close)?)?;
-    screen.set("draw_box_2",          lua.create_function(self::draw_box_2)?)?;
-    screen.set("draw_line",           lua.create_function(self::draw_line)?)?;
-    screen.set("get_screen_to_world", lua.create_function(self::get_screen_to_world)?)?;
-    screen.set("get_world_to_screen", lua.create_function(self::get_world_to_screen)?)?;
+    Export::set_table(lua, "screen", &screen)?;
 
     global.set("screen", screen)?;
 
@@ -56,14 +51,39 @@ fn wipe(lua: &mlua::Lua, color: mlua::Value) -> mlua::Result<()> {
     info = "Initialize a draw session.",
     parameter(name = "call", info = "Draw function.", kind = "function")
 )]
-fn draw(_: &mlua::Lua, call: mlua::Function) -> mlua::Result<()> {
-    unsafe {
+fn draw(lua: &mlua::Lua, call: mlua::Function) -> mlua::Result<()> {
+    draw_frame(lua, || call.call::<()>(()))
+}
+
+// Run a draw session for a whole frame, used by both "draw" and the engine-driven loop.
+pub fn draw_frame<E: From<mlua::Error>>(
+    lua: &mlua::Lua,
+    call: impl FnOnce() -> Result<(), E>,
+) -> Result<(), E> {
+    // The zone is closed before the frame boundary, so that it is counted in the frame it was drawn in.
+    Profile::scope(lua, "screen.draw", || unsafe {
         ffi::BeginDrawing();
-        let call = call.call::<()>(());
+        let call = call();
+        // Draw the profiler graph and the developer console over the frame.
+        let profile = Profile::draw(lua);
+        let console = Console::update(lua);
         ffi::EndDrawing();
 
-        call
-    }
+        call?;
+        profile?;
+        console?;
+
+        Ok::<_, E>(())
+    })?;
+
+    // Frame boundary, for the profiler.
+    Profile::frame(lua);
+
+    // Frame boundary, resume every task.
+    TaskList::update(lua)?;
+
+    // Frame boundary, check for any script change.
+    Ok(Reload::update(lua)?)
 }
 
 #[allow(non_snake_case)]
@@ -75,7 +95,7 @@ fn draw(_: &mlua::Lua, call: mlua::Function) -> mlua::Result<()> {
 )]
 fn draw_2D(lua: &mlua::Lua, (call, camera): (mlua::Function, mlua::Value)) -> mlua::Result<()> {
     unsafe {
-        let camera: Camera2D = lua.from_value(camera)?;
+        let camera = get_camera_2d(lua, camera)?;
 
         ffi::BeginMode2D(camera.into());
         let call = call.call::<()>(());
@@ -85,10 +105,15 @@ fn draw_2D(lua: &mlua::Lua, (call, camera): (mlua::Function, mlua::Value)) -> ml
     }
 }
 
-// TO-DO temporary
+#[allow(non_snake_case)]
+#[function(
+    from = "screen",
+    info = "Manually begin a 2D draw session. Use `draw_2D` whenever possible.",
+    parameter(name = "camera", info = "2D camera.", kind = "Camera2D")
+)]
 fn draw_2D_begin(lua: &mlua::Lua, camera: mlua::Value) -> mlua::Result<()> {
     unsafe {
-        let camera: Camera2D = lua.from_value(camera)?;
+        let camera = get_camera_2d(lua, camera)?;
 
         ffi::BeginMode2D(camera.into());
 
@@ -96,7 +121,11 @@ fn draw_2D_begin(lua: &mlua::Lua, camera: mlua::Value) -> mlua::Result<()> {
     }
 }
 
-// TO-DO temporary
+#[allow(non_snake_case)]
+#[function(
+    from = "screen",
+    info = "Manually close a 2D draw session. Use `draw_2D` whenever possible."
+)]
 fn draw_2D_close(_: &mlua::Lua, _: ()) -> mlua::Result<()> {
     unsafe {
         ffi::EndMode2D();
@@ -113,7 +142,7 @@ fn draw_2D_close(_: &mlua::Lua, _: ()) -> mlua::Result<()> {
 )]
 fn draw_scissor(lua: &mlua::Lua, (call, area): (mlua::Function, mlua::Value)) -> mlua::Result<()> {
     unsafe {
-        let area: Box2 = lua.from_value(area)?;
+        let area = get_box_2(lua, area)?;
 
         ffi::BeginScissorMode(
             area.p_x as i32,
@@ -135,7 +164,7 @@ fn draw_scissor(lua: &mlua::Lua, (call, area): (mlua::Function, mlua::Value)) ->
 )]
 fn draw_scissor_begin(lua: &mlua::Lua, area: mlua::Value) -> mlua::Result<()> {
     unsafe {
-        let area: Box2 = lua.from_value(area)?;
+        let area = get_box_2(lua, area)?;
 
         ffi::BeginScissorMode(
             area.p_x as i32,
@@ -193,9 +222,9 @@ fn draw_box_2(
     ),
 ) -> mlua::Result<()> {
     unsafe {
-        let box_2: Box2 = lua.from_value(box_2)?;
+        let box_2 = get_box_2(lua, box_2)?;
         let point: Vector2 = if let Some(point) = point {
-            lua.from_value(point)?
+            get_vector_2(lua, point)?
         } else {
             Vector2::default()
         };
@@ -235,8 +264,8 @@ fn draw_line(
     (source, target, thick, color): (mlua::Value, mlua::Value, Option<f32>, Option<mlua::Value>),
 ) -> mlua::Result<()> {
     unsafe {
-        let source: Vector2 = lua.from_value(source)?;
-        let target: Vector2 = lua.from_value(target)?;
+        let source = get_vector_2(lua, source)?;
+        let target = get_vector_2(lua, target)?;
         let thick = thick.unwrap_or(1.0);
         let color: Color = if let Some(color) = color {
             lua.from_value(color)?
@@ -261,8 +290,8 @@ fn get_world_to_screen(
     (point, camera): (mlua::Value, mlua::Value),
 ) -> mlua::Result<mlua::Value> {
     unsafe {
-        let point: Vector2 = lua.from_value(point)?;
-        let camera: Camera2D = lua.from_value(camera)?;
+        let point = get_vector_2(lua, point)?;
+        let camera = get_camera_2d(lua, camera)?;
 
         lua.to_value(&Vector2::from(ffi::GetWorldToScreen2D(
             point.into(),
@@ -282,8 +311,8 @@ fn get_screen_to_world(
     (point, camera): (mlua::Value, mlua::Value),
 ) -> mlua::Result<mlua::Value> {
     unsafe {
-        let point: Vector2 = lua.from_value(point)?;
-        let camera: Camera2D = lua.from_value(camera)?;
+        let point = get_vector_2(lua, point)?;
+        let camera = get_camera_2d(lua, camera)?;
 
         lua.to_value(&Vector2::from(ffi::GetScreenToWorld2D(
             point.into(),
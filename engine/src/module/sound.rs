@@ -1,5 +1,6 @@
 use crate::module::archive::*;
 use crate::module::general::*;
+use crate::module::vfs::*;
 use engine_macro::*;
 
 //================================================================
@@ -13,10 +14,7 @@ use raylib::prelude::*;
 pub fn set_global(lua: &mlua::Lua, global: &mlua::Table) -> anyhow::Result<()> {
     let sound = lua.create_table()?;
 
-    sound.set("get_master_volume", lua.create_function(self::get_master_volume)?)?;
-    sound.set("set_master_volume", lua.create_function(self::set_master_volume)?)?;
-    sound.set("new",               lua.create_function(self::Sound::new)?)?;
-    sound.set("new_archive",       lua.create_function(self::Sound::new_archive)?)?;
+    Export::set_table(lua, "sound", &sound)?;
 
     global.set("sound", sound)?;
 
@@ -55,6 +53,55 @@ struct Sound {
     alias: Vec<ffi::Sound>,
 }
 
+impl Sound {
+    fn from_memory(
+        data: &[u8],
+        extension: &str,
+        count: Option<usize>,
+        call: &str,
+        path: &str,
+    ) -> mlua::Result<Self> {
+        unsafe {
+            let wave = ffi::LoadWaveFromMemory(
+                c_string(extension)?.as_ptr(),
+                data.as_ptr(),
+                data.len() as i32,
+            );
+            let inner = ffi::LoadSoundFromWave(wave);
+            ffi::UnloadWave(wave);
+
+            Self::from_sound(inner, count, call, path)
+        }
+    }
+
+    // Create every alias of a sound, if the sound is valid.
+    fn from_sound(
+        inner: ffi::Sound,
+        count: Option<usize>,
+        call: &str,
+        path: &str,
+    ) -> mlua::Result<Self> {
+        unsafe {
+            let mut alias = Vec::new();
+
+            if ffi::IsSoundValid(inner) {
+                if let Some(count) = count {
+                    for _ in 0..count {
+                        alias.push(ffi::LoadSoundAlias(inner));
+                    }
+                }
+
+                Ok(Self { inner, alias })
+            } else {
+                Err(mlua::Error::external(format!(
+                    "{call}(): Error loading sound \"{path}\"."
+                )))
+            }
+        }
+    }
+}
+
+#[export]
 impl Sound {
     #[function(
         from = "sound",
@@ -72,24 +119,18 @@ impl Sound {
             kind(user_data(name = "Sound"))
         )
     )]
-    fn new(_: &mlua::Lua, (path, count): (String, Option<usize>)) -> mlua::Result<Self> {
-        unsafe {
-            let inner = ffi::LoadSound(c_string(&path)?.as_ptr());
-            let mut alias = Vec::new();
+    fn new(lua: &mlua::Lua, (path, count): (String, Option<usize>)) -> mlua::Result<Self> {
+        let file = match Vfs::get_file(lua, &path)? {
+            VfsFile::Path(file) => file,
+            VfsFile::Data(data, extension) => {
+                return Self::from_memory(&data, &extension, count, "sound.new", &path);
+            }
+        };
 
-            if ffi::IsSoundValid(inner) {
-                if let Some(count) = count {
-                    for _ in 0..count {
-                        alias.push(ffi::LoadSoundAlias(inner));
-                    }
-                }
+        unsafe {
+            let inner = ffi::LoadSound(c_string(&file)?.as_ptr());
 
-                Ok(Self { inner, alias })
-            } else {
-                Err(mlua::Error::external(format!(
-                    "sound.new(): Error loading sound \"{path}\"."
-                )))
-            }
+            Self::from_sound(inner, count, "sound.new", &path)
         }
     }
 
@@ -120,29 +161,7 @@ impl Sound {
     ) -> mlua::Result<Self> {
         let (data, extension) = Archive::borrow_file(&path, archive)?;
 
-        unsafe {
-            let inner = ffi::LoadWaveFromMemory(
-                c_string(&extension)?.as_ptr(),
-                data.as_ptr(),
-                data.len() as i32,
-            );
-            let inner = ffi::LoadSoundFromWave(inner);
-            let mut alias = Vec::new();
-
-            if ffi::IsSoundValid(inner) {
-                if let Some(count) = count {
-                    for _ in 0..count {
-                        alias.push(ffi::LoadSoundAlias(inner));
-                    }
-                }
-
-                Ok(Self { inner, alias })
-            } else {
-                Err(mlua::Error::external(format!(
-                    "sound.new_archive(): Error loading sound \"{path}\"."
-                )))
-            }
-        }
+        Self::from_memory(&data, &extension, count, "sound.new_archive", &path)
     }
 
     #[method(
@@ -361,18 +380,3 @@ impl Drop for Sound {
         }
     }
 }
-
-impl mlua::UserData for Sound {
-    #[rustfmt::skip]
-    fn add_methods<M: mlua::UserDataMethods<Self>>(method: &mut M) {
-        method.add_method("play",            Self::play);
-        method.add_method("stop",            Self::stop);
-        method.add_method("pause",           Self::pause);
-        method.add_method("resume",          Self::resume);
-        method.add_method("is_play",         Self::is_play);
-        method.add_method("get_alias_count", Self::get_alias_count);
-        method.add_method("set_volume",      Self::set_volume);
-        method.add_method("set_pitch",       Self::set_pitch);
-        method.add_method("set_pan",         Self::set_pan);
-    }
-}
@@ -0,0 +1,374 @@
+use crate::module::general::*;
+use engine_macro::*;
+
+//================================================================
+
+use std::cell::Cell;
+use std::future::Future;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::task::Context;
+use std::task::Poll;
+use std::task::Waker;
+use std::time::Instant;
+
+//================================================================
+
+#[rustfmt::skip]
+#[module(name = "task", info = "Task API. Every task is a coroutine, resumed once per frame.")]
+pub fn set_global(lua: &mlua::Lua, global: &mlua::Table) -> anyhow::Result<()> {
+    let task = lua.create_table()?;
+
+    Export::set_table(lua, "task", &task)?;
+
+    global.set("task", task)?;
+
+    Ok(())
+}
+
+//================================================================
+
+#[derive(Clone, Copy, PartialEq)]
+enum TaskState {
+    Active,
+    Done,
+    Cancel,
+}
+
+struct TaskEntry {
+    thread: Pin<Box<mlua::AsyncThread<()>>>,
+    state: Rc<Cell<TaskState>>,
+}
+
+pub struct TaskList {
+    list: Vec<TaskEntry>,
+    start: Instant,
+    // Time (in seconds) and frame count as of the last update.
+    time: f64,
+    frame: u64,
+}
+
+impl TaskList {
+    fn new() -> Self {
+        Self {
+            list: Vec::new(),
+            start: Instant::now(),
+            time: 0.0,
+            frame: 0,
+        }
+    }
+
+    // Resume every task. Must be called once per frame.
+    pub fn update(lua: &mlua::Lua) -> mlua::Result<()> {
+        // Take the list out, as resuming a task will run Lua code, which could create a new task.
+        let list = if let Some(mut task) = lua.app_data_mut::<Self>() {
+            task.time = task.start.elapsed().as_secs_f64();
+            task.frame += 1;
+
+            std::mem::take(&mut task.list)
+        } else {
+            return Ok(());
+        };
+
+        let mut context = Context::from_waker(Waker::noop());
+        let mut keep = Vec::new();
+        let mut error = None;
+
+        for mut entry in list {
+            if entry.state.get() == TaskState::Cancel {
+                continue;
+            }
+
+            // Stop at the first error, the remaining tasks will be resumed on the next frame.
+            if error.is_some() {
+                keep.push(entry);
+                continue;
+            }
+
+            // A task is pending whenever it yields, either through a wait function or through "coroutine.yield".
+            match entry.thread.as_mut().poll(&mut context) {
+                Poll::Pending => keep.push(entry),
+                Poll::Ready(result) => {
+                    entry.state.set(TaskState::Done);
+
+                    if let Err(value) = result {
+                        error = Some(value);
+                    }
+                }
+            }
+        }
+
+        if let Some(mut task) = lua.app_data_mut::<Self>() {
+            keep.append(&mut task.list);
+            task.list = keep;
+        }
+
+        if let Some(error) = error {
+            return Err(error);
+        }
+
+        Ok(())
+    }
+
+    fn get_time(lua: &mlua::Lua) -> f64 {
+        lua.app_data_ref::<Self>()
+            .map(|task| task.time)
+            .unwrap_or_default()
+    }
+
+    fn get_frame(lua: &mlua::Lua) -> u64 {
+        lua.app_data_ref::<Self>()
+            .map(|task| task.frame)
+            .unwrap_or_default()
+    }
+}
+
+//================================================================
+
+#[class(info = "Task class.")]
+struct Task {
+    state: Rc<Cell<TaskState>>,
+}
+
+#[export]
+impl Task {
+    #[function(
+        from = "task",
+        info = "Create a new task. The task will first run on the next frame.",
+        parameter(name = "call", info = "Task function.", kind = "function"),
+        parameter(
+            name = "...",
+            info = "Argument list to pass to the task function.",
+            kind = "any"
+        ),
+        result(name = "task", info = "Task.", kind(user_data(name = "Task")))
+    )]
+    fn new(
+        lua: &mlua::Lua,
+        (call, argument): (mlua::Function, mlua::MultiValue),
+    ) -> mlua::Result<Self> {
+        let thread = lua.create_thread(call)?.into_async::<()>(argument)?;
+        let state = Rc::new(Cell::new(TaskState::Active));
+
+        if lua.app_data_ref::<TaskList>().is_none() {
+            lua.set_app_data(TaskList::new());
+        }
+
+        if let Some(mut task) = lua.app_data_mut::<TaskList>() {
+            task.list.push(TaskEntry {
+                thread: Box::pin(thread),
+                state: state.clone(),
+            });
+        }
+
+        Ok(Self { state })
+    }
+
+    #[method(
+        from = "Task",
+        info = "Cancel the task. The task will not be resumed again."
+    )]
+    fn cancel(_: &mlua::Lua, this: &Self, _: ()) -> mlua::Result<()> {
+        if this.state.get() == TaskState::Active {
+            this.state.set(TaskState::Cancel);
+        }
+
+        Ok(())
+    }
+
+    #[method(
+        from = "Task",
+        info = "Check if the task has finished, or has been cancelled.",
+        result(name = "done", info = "True if finished.", kind = "boolean")
+    )]
+    fn is_done(_: &mlua::Lua, this: &Self, _: ()) -> mlua::Result<bool> {
+        Ok(this.state.get() != TaskState::Active)
+    }
+}
+
+//================================================================
+
+#[function(
+    from = "task",
+    info = "Wait for a given time. Can only be called from within a task.",
+    parameter(name = "time", info = "Time to wait (in seconds).", kind = "number")
+)]
+async fn wait(lua: mlua::Lua, time: f64) -> mlua::Result<()> {
+    let time = TaskList::get_time(&lua) + time;
+
+    std::future::poll_fn(|_| {
+        if TaskList::get_time(&lua) >= time {
+            Poll::Ready(Ok(()))
+        } else {
+            Poll::Pending
+        }
+    })
+    .await
+}
+
+#[function(
+    from = "task",
+    info = "Wait for a given frame count. Can only be called from within a task.",
+    parameter(name = "count", info = "Frame count to wait.", kind = "number")
+)]
+async fn wait_frames(lua: mlua::Lua, count: u64) -> mlua::Result<()> {
+    let frame = TaskList::get_frame(&lua) + count;
+
+    std::future::poll_fn(|_| {
+        if TaskList::get_frame(&lua) >= frame {
+            Poll::Ready(Ok(()))
+        } else {
+            Poll::Pending
+        }
+    })
+    .await
+}
+
+#[function(
+    from = "task",
+    info = "Wait until a function returns true. The function is called once per frame. Can only be called from within a task.",
+    parameter(name = "call", info = "Condition function.", kind = "function")
+)]
+async fn wait_until(_: mlua::Lua, call: mlua::Function) -> mlua::Result<()> {
+    std::future::poll_fn(|_| match call.call::<bool>(()) {
+        Ok(true) => Poll::Ready(Ok(())),
+        Ok(false) => Poll::Pending,
+        Err(error) => Poll::Ready(Err(error)),
+    })
+    .await
+}
+
+//================================================================
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn get_lua() -> mlua::Lua {
+        let lua = mlua::Lua::new();
+        set_global(&lua, &lua.globals()).expect("Error setting module.");
+
+        lua.load("order = {}").exec().expect("Error running code.");
+
+        lua
+    }
+
+    fn get_order(lua: &mlua::Lua) -> String {
+        lua.load("return table.concat(order, \" \")")
+            .eval()
+            .expect("Error running code.")
+    }
+
+    #[test]
+    fn update() {
+        let lua = get_lua();
+
+        lua.load(
+            r#"
+            task.new(function(name)
+                table.insert(order, name .. "1")
+                coroutine.yield()
+                table.insert(order, name .. "2")
+            end, "a")
+
+            task.new(function()
+                table.insert(order, "b1")
+                -- A task created from within a task runs on the next update.
+                task.new(function() table.insert(order, "c1") end)
+            end)
+            "#,
+        )
+        .exec()
+        .expect("Error running code.");
+
+        // No task is run until the first update.
+        assert_eq!(get_order(&lua), "");
+
+        TaskList::update(&lua).unwrap();
+        assert_eq!(get_order(&lua), "a1 b1");
+
+        TaskList::update(&lua).unwrap();
+        assert_eq!(get_order(&lua), "a1 b1 a2 c1");
+
+        TaskList::update(&lua).unwrap();
+        assert_eq!(get_order(&lua), "a1 b1 a2 c1");
+    }
+
+    #[test]
+    fn cancel() {
+        let lua = get_lua();
+
+        lua.load(
+            r#"
+            value = task.new(function()
+                while true do
+                    table.insert(order, "a")
+                    coroutine.yield()
+                end
+            end)
+            "#,
+        )
+        .exec()
+        .expect("Error running code.");
+
+        TaskList::update(&lua).unwrap();
+        TaskList::update(&lua).unwrap();
+
+        lua.load("value:cancel()")
+            .exec()
+            .expect("Error running code.");
+
+        TaskList::update(&lua).unwrap();
+
+        assert_eq!(get_order(&lua), "a a");
+        assert!(lua.load("return value:is_done()").eval::<bool>().unwrap());
+    }
+
+    #[test]
+    fn error() {
+        let lua = get_lua();
+
+        lua.load(
+            r#"
+            task.new(function() error("failure") end)
+            value = task.new(function() table.insert(order, "a") end)
+            "#,
+        )
+        .exec()
+        .expect("Error running code.");
+
+        let error = TaskList::update(&lua).unwrap_err();
+        assert!(error.to_string().contains("failure"));
+
+        // The task after the failing one is kept, and resumed on the next update.
+        assert_eq!(get_order(&lua), "");
+        assert!(!lua.load("return value:is_done()").eval::<bool>().unwrap());
+
+        TaskList::update(&lua).unwrap();
+        assert_eq!(get_order(&lua), "a");
+    }
+
+    #[test]
+    fn wait_frames() {
+        let lua = get_lua();
+
+        lua.load(
+            r#"
+            task.new(function()
+                task.wait_frames(2)
+                table.insert(order, "a")
+            end)
+            "#,
+        )
+        .exec()
+        .expect("Error running code.");
+
+        // The first update starts the wait, the wait is over two frames later.
+        TaskList::update(&lua).unwrap();
+        TaskList::update(&lua).unwrap();
+        assert_eq!(get_order(&lua), "");
+
+        TaskList::update(&lua).unwrap();
+        assert_eq!(get_order(&lua), "a");
+    }
+}
@@ -1,5 +1,6 @@
 use crate::module::archive::*;
 use crate::module::general::*;
+use crate::module::vfs::*;
 use engine_macro::*;
 
 //================================================================
@@ -17,11 +18,9 @@ pub fn set_global(lua: &mlua::Lua, global: &mlua::Table) -> anyhow::Result<()> {
     let texture        = lua.create_table()?;
     let texture_target = lua.create_table()?;
 
-    texture.set("new",         lua.create_function(self::Texture::new)?)?;
-    texture.set("new_archive", lua.create_function(self::Texture::new_archive)?)?;
-    texture.set("draw_batch",  lua.create_function(self::Texture::draw_batch)?)?;
+    Export::set_table(lua, "texture", &texture)?;
 
-    texture_target.set("new",  lua.create_function(self::TextureTarget::new)?)?;
+    Export::set_table(lua, "texture_target", &texture_target)?;
 
     global.set("texture",        texture)?;
     global.set("texture_target", texture_target)?;
@@ -29,10 +28,40 @@ pub fn set_global(lua: &mlua::Lua, global: &mlua::Table) -> anyhow::Result<()> {
     Ok(())
 }
 
+// Create the FFI texture batch buffer constructor. Only for a full state, as FFI data is not bounds-checked.
+pub fn set_batch(lua: &mlua::Lua) -> mlua::Result<()> {
+    let call: mlua::Function = lua.load(BATCH_CODE).set_name("=batch").eval()?;
+
+    lua.set_named_registry_value(BATCH_NAME, call)
+}
+
 //================================================================
 
+const BATCH_NAME: &str = "flak_texture_batch";
+// Must match the layout of TextureBatch.
+const BATCH_CODE: &str = r#"
+local ffi = require("ffi")
+
+ffi.cdef([[
+typedef struct {
+    uint32_t identifier;
+    uint32_t index;
+    float s_p_x, s_p_y, s_s_x, s_s_y;
+    float t_p_x, t_p_y, t_s_x, t_s_y;
+    float p_x, p_y;
+    int32_t s_x, s_y;
+    float angle;
+    uint8_t r, g, b, a;
+} flak_texture_batch;
+]])
+
+return function(length)
+    return ffi.new("flak_texture_batch[?]", length)
+end
+"#;
+
 #[repr(C)]
-#[derive(Debug)]
+#[derive(Debug, Default, Clone)]
 struct TextureBatch {
     // Texture ID.
     identifier: u32,
@@ -63,11 +92,166 @@ struct TextureBatch {
     a: u8,
 }
 
+impl mlua::FromLua for TextureBatch {
+    // Read every field directly, rather than through serde, as an entry is converted on every call. Any missing
+    // field is zero.
+    fn from_lua(value: mlua::Value, lua: &mlua::Lua) -> mlua::Result<Self> {
+        let table = mlua::Table::from_lua(value, lua)?;
+
+        Ok(Self {
+            identifier: Self::get_field(&table, "identifier")?,
+            index: Self::get_field(&table, "index")?,
+            s_p_x: Self::get_field(&table, "s_p_x")?,
+            s_p_y: Self::get_field(&table, "s_p_y")?,
+            s_s_x: Self::get_field(&table, "s_s_x")?,
+            s_s_y: Self::get_field(&table, "s_s_y")?,
+            t_p_x: Self::get_field(&table, "t_p_x")?,
+            t_p_y: Self::get_field(&table, "t_p_y")?,
+            t_s_x: Self::get_field(&table, "t_s_x")?,
+            t_s_y: Self::get_field(&table, "t_s_y")?,
+            p_x: Self::get_field(&table, "p_x")?,
+            p_y: Self::get_field(&table, "p_y")?,
+            s_x: Self::get_field(&table, "s_x")?,
+            s_y: Self::get_field(&table, "s_y")?,
+            angle: Self::get_field(&table, "angle")?,
+            r: Self::get_field(&table, "r")?,
+            g: Self::get_field(&table, "g")?,
+            b: Self::get_field(&table, "b")?,
+            a: Self::get_field(&table, "a")?,
+        })
+    }
+}
+
+impl TextureBatch {
+    // Get a field of an entry table, or zero if missing.
+    fn get_field<T: mlua::FromLua + Default>(table: &mlua::Table, name: &str) -> mlua::Result<T> {
+        Ok(table.get::<Option<T>>(name)?.unwrap_or_default())
+    }
+
+    #[rustfmt::skip]
+    fn draw(buffer: &mut [Self]) {
+        let mut texture: ffi::Texture2D = unsafe { MaybeUninit::zeroed().assume_init() };
+
+        buffer.sort_by(|a, b| {
+            if a.index != b.index {
+                a.index.cmp(&b.index)
+            } else {
+                a.identifier.cmp(&b.identifier)
+            }
+        });
+
+        for entry in buffer {
+            unsafe {
+                texture.id     = entry.identifier;
+                texture.width  = entry.s_x;
+                texture.height = entry.s_y;
+
+                ffi::DrawTexturePro(
+                    texture,
+                    ffi::Rectangle {
+                        x:      entry.s_p_x,
+                        y:      entry.s_p_y,
+                        width:  entry.s_s_x,
+                        height: entry.s_s_y,
+                    },
+                    ffi::Rectangle {
+                        x:      entry.t_p_x,
+                        y:      entry.t_p_y,
+                        width:  entry.t_s_x,
+                        height: entry.t_s_y,
+                    },
+                    ffi::Vector2 {
+                        x: entry.p_x,
+                        y: entry.p_y,
+                    },
+                    entry.angle,
+                    ffi::Color {
+                        r: entry.r,
+                        g: entry.g,
+                        b: entry.b,
+                        a: entry.a,
+                    },
+                );
+            }
+        }
+    }
+}
+
+#[class(
+    info = "Texture batch buffer, for use with draw_batch in safe mode, in place of the FFI buffer. Every entry is bounds-checked."
+)]
+struct TextureBatchBuffer {
+    list: Vec<TextureBatch>,
+}
+
+#[export]
+impl TextureBatchBuffer {
+    #[method(
+        from = "TextureBatchBuffer",
+        info = "Set an entry. Every field is the same as in the FFI buffer, and any missing field is zero.",
+        parameter(
+            name = "index",
+            info = "Entry index, starting from 0.",
+            kind = "number"
+        ),
+        parameter(name = "entry", info = "Entry table.", kind = "table")
+    )]
+    fn set(
+        _: &mlua::Lua,
+        this: &mut Self,
+        (index, entry): (usize, TextureBatch),
+    ) -> mlua::Result<()> {
+        let length = this.list.len();
+
+        let Some(value) = this.list.get_mut(index) else {
+            return Err(mlua::Error::runtime(format!(
+                "TextureBatchBuffer:set(): Index {index} is out of bounds, for length {length}."
+            )));
+        };
+
+        *value = entry;
+
+        Ok(())
+    }
+
+    #[method(
+        from = "TextureBatchBuffer",
+        info = "Get the buffer length.",
+        result(name = "length", info = "Buffer length.", kind = "number")
+    )]
+    fn get_length(_: &mlua::Lua, this: &Self, _: ()) -> mlua::Result<usize> {
+        Ok(this.list.len())
+    }
+}
+
 #[class(info = "Texture class.")]
 struct Texture {
     inner: ffi::Texture2D,
 }
 
+impl Texture {
+    fn from_memory(data: &[u8], extension: &str, call: &str, path: &str) -> mlua::Result<Self> {
+        unsafe {
+            let inner = ffi::LoadImageFromMemory(
+                c_string(extension)?.as_ptr(),
+                data.as_ptr(),
+                data.len() as i32,
+            );
+            let texture = ffi::LoadTextureFromImage(inner);
+            ffi::UnloadImage(inner);
+
+            if ffi::IsTextureValid(texture) {
+                Ok(Self { inner: texture })
+            } else {
+                Err(mlua::Error::external(format!(
+                    "{call}(): Error loading texture \"{path}\"."
+                )))
+            }
+        }
+    }
+}
+
+#[export]
 impl Texture {
     #[function(
         from = "texture",
@@ -79,9 +263,16 @@ impl Texture {
             kind(user_data(name = "Texture"))
         )
     )]
-    fn new(_: &mlua::Lua, path: String) -> mlua::Result<Self> {
+    fn new(lua: &mlua::Lua, path: String) -> mlua::Result<Self> {
+        let file = match Vfs::get_file(lua, &path)? {
+            VfsFile::Path(file) => file,
+            VfsFile::Data(data, extension) => {
+                return Self::from_memory(&data, &extension, "texture.new", &path);
+            }
+        };
+
         unsafe {
-            let inner = ffi::LoadTexture(c_string(&path)?.as_ptr());
+            let inner = ffi::LoadTexture(c_string(&file)?.as_ptr());
 
             if ffi::IsTextureValid(inner) {
                 Ok(Self { inner })
@@ -114,22 +305,33 @@ impl Texture {
     ) -> mlua::Result<Self> {
         let (data, extension) = Archive::borrow_file(&path, archive)?;
 
-        unsafe {
-            let inner = ffi::LoadImageFromMemory(
-                c_string(&extension)?.as_ptr(),
-                data.as_ptr(),
-                data.len() as i32,
-            );
-            let inner = ffi::LoadTextureFromImage(inner);
+        Self::from_memory(&data, &extension, "texture.new_archive", &path)
+    }
 
-            if ffi::IsTextureValid(inner) {
-                Ok(Self { inner })
-            } else {
-                Err(mlua::Error::external(format!(
-                    "texture.new_archive(): Error loading texture \"{path}\"."
-                )))
-            }
+    #[function(
+        from = "texture",
+        info = "Create a new texture batch buffer, for use with draw_batch. In safe mode, the buffer is a TextureBatchBuffer rather than FFI data.",
+        parameter(name = "length", info = "Texture batch length.", kind = "number"),
+        result(
+            name = "buffer",
+            info = "Texture batch buffer.",
+            kind(union(
+                kind(user_data(name = "cdata")),
+                kind(user_data(name = "TextureBatchBuffer"))
+            ))
+        )
+    )]
+    fn new_batch(lua: &mlua::Lua, length: usize) -> mlua::Result<mlua::Value> {
+        // The FFI constructor is only set for a full state.
+        if let Some(call) = lua.named_registry_value::<Option<mlua::Function>>(BATCH_NAME)? {
+            return call.call(length);
         }
+
+        Ok(mlua::Value::UserData(lua.create_userdata(
+            TextureBatchBuffer {
+                list: vec![TextureBatch::default(); length],
+            },
+        )?))
     }
 
     #[function(
@@ -137,59 +339,44 @@ impl Texture {
         info = "Perform a batch draw.",
         parameter(
             name = "buffer",
-            info = "Texture batch buffer pointer.",
-            kind(user_data(name = "cdata"))
+            info = "Texture batch buffer, from new_batch.",
+            kind(union(
+                kind(user_data(name = "cdata")),
+                kind(user_data(name = "TextureBatchBuffer"))
+            ))
         ),
         parameter(name = "length", info = "Texture batch length.", kind = "number")
     )]
-    #[rustfmt::skip]
-    fn draw_batch(_: &mlua::Lua, (buffer, length): (mlua::Value, usize)) -> mlua::Result<()> {
-        let buffer = buffer.to_pointer();
-        let buffer =
-            unsafe { std::slice::from_raw_parts_mut(buffer as *mut TextureBatch, length) };
-
-        let mut texture: ffi::Texture2D = unsafe { MaybeUninit::zeroed().assume_init() };
-
-        buffer.sort_by(|a, b| {
-            if a.index != b.index {
-                a.index.cmp(&b.index)
-            } else {
-                a.identifier.cmp(&b.identifier)
+    fn draw_batch(lua: &mlua::Lua, (buffer, length): (mlua::Value, usize)) -> mlua::Result<()> {
+        match buffer {
+            mlua::Value::UserData(buffer) => {
+                let mut buffer = buffer.borrow_mut::<TextureBatchBuffer>()?;
+
+                if length > buffer.list.len() {
+                    return Err(mlua::Error::runtime(format!(
+                        "texture.draw_batch(): Length {length} is greater than buffer length {}.",
+                        buffer.list.len()
+                    )));
+                }
+
+                TextureBatch::draw(&mut buffer.list[..length]);
             }
-        });
-
-        for entry in buffer {
-            unsafe {
-                texture.id     = entry.identifier;
-                texture.width  = entry.s_x;
-                texture.height = entry.s_y;
-
-                ffi::DrawTexturePro(
-                    texture,
-                    ffi::Rectangle {
-                        x:      entry.s_p_x,
-                        y:      entry.s_p_y,
-                        width:  entry.s_s_x,
-                        height: entry.s_s_y,
-                    },
-                    ffi::Rectangle {
-                        x:      entry.t_p_x,
-                        y:      entry.t_p_y,
-                        width:  entry.t_s_x,
-                        height: entry.t_s_y,
-                    },
-                    ffi::Vector2 {
-                        x: entry.p_x,
-                        y: entry.p_y,
-                    },
-                    entry.angle,
-                    ffi::Color {
-                        r: entry.r,
-                        g: entry.g,
-                        b: entry.b,
-                        a: entry.a,
-                    },
-                );
+            // FFI data is not bounds-checked, so it is only accepted in a full state, where the FFI constructor is set.
+            mlua::Value::Other(_)
+                if lua
+                    .named_registry_value::<Option<mlua::Function>>(BATCH_NAME)?
+                    .is_some() =>
+            {
+                let buffer = buffer.to_pointer();
+                let buffer =
+                    unsafe { std::slice::from_raw_parts_mut(buffer as *mut TextureBatch, length) };
+
+                TextureBatch::draw(buffer);
+            }
+            _ => {
+                return Err(mlua::Error::runtime(
+                    "texture.draw_batch(): Buffer is not a texture batch buffer.",
+                ));
             }
         }
 
@@ -217,9 +404,9 @@ impl Texture {
         ),
     ) -> mlua::Result<()> {
         unsafe {
-            let source: Box2 = lua.from_value(source)?;
-            let target: Box2 = lua.from_value(target)?;
-            let point: Vector2 = lua.from_value(point)?;
+            let source = get_box_2(lua, source)?;
+            let target = get_box_2(lua, target)?;
+            let point = get_vector_2(lua, point)?;
             let color: Color = lua.from_value(color)?;
 
             ffi::DrawTexturePro(
@@ -265,15 +452,6 @@ impl Drop for Texture {
     }
 }
 
-impl mlua::UserData for Texture {
-    #[rustfmt::skip]
-    fn add_methods<M: mlua::UserDataMethods<Self>>(method: &mut M) {
-        method.add_method("draw",            Self::draw);
-        method.add_method("get_identifier",  Self::get_identifier);
-        method.add_method("get_scale",       Self::get_scale);
-    }
-}
-
 //================================================================
 
 #[class(info = "Texture (render-target) class.")]
@@ -281,6 +459,7 @@ struct TextureTarget {
     inner: ffi::RenderTexture,
 }
 
+#[export]
 impl TextureTarget {
     #[function(
         from = "texture_target",
@@ -298,7 +477,7 @@ impl TextureTarget {
     )]
     fn new(lua: &mlua::Lua, scale: mlua::Value) -> mlua::Result<Self> {
         unsafe {
-            let scale: Vector2 = lua.from_value(scale)?;
+            let scale = get_vector_2(lua, scale)?;
             let inner = ffi::LoadRenderTexture(scale.x as i32, scale.y as i32);
 
             if ffi::IsRenderTextureValid(inner) {
@@ -347,9 +526,9 @@ impl TextureTarget {
         ),
     ) -> mlua::Result<()> {
         unsafe {
-            let mut source: Box2 = lua.from_value(source)?;
-            let target: Box2 = lua.from_value(target)?;
-            let point: Vector2 = lua.from_value(point)?;
+            let mut source = get_box_2(lua, source)?;
+            let target = get_box_2(lua, target)?;
+            let point = get_vector_2(lua, point)?;
             let color: Color = lua.from_value(color)?;
 
             source.s_y = -source.s_y;
@@ -387,12 +566,3 @@ impl Drop for TextureTarget {
         }
     }
 }
-
-impl mlua::UserData for TextureTarget {
-    #[rustfmt::skip]
-    fn add_methods<M: mlua::UserDataMethods<Self>>(method: &mut M) {
-        method.add_method("begin",     Self::begin);
-        method.add_method("draw",      Self::draw);
-        method.add_method("get_scale", Self::get_scale);
-    }
-}
@@ -0,0 +1,397 @@
+use crate::module::general::*;
+use engine_macro::*;
+
+//================================================================
+
+use raylib::prelude::*;
+
+//================================================================
+
+#[rustfmt::skip]
+#[module(name = "vector2", info = "Vector2 API.")]
+pub fn set_global(lua: &mlua::Lua, global: &mlua::Table) -> anyhow::Result<()> {
+    let vector2 = lua.create_table()?;
+
+    Export::set_table(lua, "vector2", &vector2)?;
+
+    global.set("vector2", vector2)?;
+
+    Ok(())
+}
+
+//================================================================
+
+#[class(
+    name = "Vector2",
+    info = "Native 2D vector. Accepted in place of a Vector2 table by every function."
+)]
+#[derive(Clone, Copy)]
+pub struct NativeVector2 {
+    pub inner: Vector2,
+}
+
+impl mlua::FromLua for NativeVector2 {
+    fn from_lua(value: mlua::Value, _: &mlua::Lua) -> mlua::Result<Self> {
+        match value {
+            mlua::Value::UserData(data) => Ok(*data.borrow::<Self>()?),
+            // Read every field directly, rather than through serde, as a table is converted on every call.
+            mlua::Value::Table(table) => Ok(Self {
+                inner: Vector2::new(table.get("x")?, table.get("y")?),
+            }),
+            value => Err(mlua::Error::FromLuaConversionError {
+                from: value.type_name(),
+                to: "Vector2".to_string(),
+                message: None,
+            }),
+        }
+    }
+}
+
+#[export]
+impl NativeVector2 {
+    #[function(
+        from = "vector2",
+        info = "Create a new native Vector2.",
+        parameter(name = "x", info = "X component.", kind = "number"),
+        parameter(name = "y", info = "Y component.", kind = "number"),
+        result(name = "vector", info = "Vector2.", kind(user_data(name = "Vector2")))
+    )]
+    fn new(_: &mlua::Lua, (x, y): (f32, f32)) -> mlua::Result<Self> {
+        Ok(Self {
+            inner: Vector2::new(x, y),
+        })
+    }
+
+    #[method(
+        from = "Vector2",
+        info = "Get the length of the vector.",
+        result(name = "length", info = "Vector length.", kind = "number")
+    )]
+    fn length(_: &mlua::Lua, this: &Self, _: ()) -> mlua::Result<f32> {
+        Ok(this.inner.length())
+    }
+
+    #[method(
+        from = "Vector2",
+        info = "Get the vector, with a length of one. A zero vector is left as is.",
+        result(
+            name = "vector",
+            info = "Normal vector.",
+            kind(user_data(name = "Vector2"))
+        )
+    )]
+    fn normalize(_: &mlua::Lua, this: &Self, _: ()) -> mlua::Result<Self> {
+        Ok(Self {
+            inner: this.inner.normalized(),
+        })
+    }
+
+    #[method(
+        from = "Vector2",
+        info = "Get the dot product of the vector and another vector.",
+        parameter(name = "other", info = "Other vector.", kind = "Vector2"),
+        result(name = "dot", info = "Dot product.", kind = "number")
+    )]
+    fn dot(_: &mlua::Lua, this: &Self, other: Self) -> mlua::Result<f32> {
+        Ok(this.inner.dot(other.inner))
+    }
+
+    #[method(
+        from = "Vector2",
+        info = "Linearly interpolate from the vector to another vector.",
+        parameter(name = "other", info = "Other vector.", kind = "Vector2"),
+        parameter(
+            name = "time",
+            info = "Time, from 0.0 (vector) to 1.0 (other vector).",
+            kind = "number"
+        ),
+        result(
+            name = "vector",
+            info = "Interpolated vector.",
+            kind(user_data(name = "Vector2"))
+        )
+    )]
+    fn lerp(_: &mlua::Lua, this: &Self, (other, time): (Self, f32)) -> mlua::Result<Self> {
+        Ok(Self {
+            inner: this.inner.lerp(other.inner, time),
+        })
+    }
+
+    #[method(
+        from = "Vector2",
+        info = "Rotate the vector.",
+        parameter(name = "angle", info = "Angle, in degrees.", kind = "number"),
+        result(
+            name = "vector",
+            info = "Rotated vector.",
+            kind(user_data(name = "Vector2"))
+        )
+    )]
+    fn rotate(_: &mlua::Lua, this: &Self, angle: f32) -> mlua::Result<Self> {
+        Ok(Self {
+            inner: this.inner.rotated(angle.to_radians()),
+        })
+    }
+
+    //================================================================
+
+    #[method(
+        from = "Vector2",
+        info = "Add two vectors.",
+        operator = "add",
+        parameter(name = "other", info = "Other vector.", kind = "Vector2"),
+        result(
+            name = "vector",
+            info = "Vector sum.",
+            kind(user_data(name = "Vector2"))
+        )
+    )]
+    fn add(_: &mlua::Lua, this: &Self, other: Self) -> mlua::Result<Self> {
+        Ok(Self {
+            inner: this.inner + other.inner,
+        })
+    }
+
+    #[method(
+        from = "Vector2",
+        info = "Subtract a vector from the vector.",
+        operator = "sub",
+        parameter(name = "other", info = "Other vector.", kind = "Vector2"),
+        result(
+            name = "vector",
+            info = "Vector difference.",
+            kind(user_data(name = "Vector2"))
+        )
+    )]
+    fn sub(_: &mlua::Lua, this: &Self, other: Self) -> mlua::Result<Self> {
+        Ok(Self {
+            inner: this.inner - other.inner,
+        })
+    }
+
+    #[method(
+        from = "Vector2",
+        info = "Multiply the vector by a number, or by another vector (per component). The number may be on either side.",
+        operator = "mul",
+        parameter(
+            name = "other",
+            info = "Number, or other vector.",
+            kind(union(kind = "number", kind = "Vector2"))
+        ),
+        result(
+            name = "vector",
+            info = "Vector product.",
+            kind(user_data(name = "Vector2"))
+        )
+    )]
+    fn mul(lua: &mlua::Lua, this: mlua::Value, other: mlua::Value) -> mlua::Result<Self> {
+        let inner = match (get_number(&this), get_number(&other)) {
+            (Some(this), _) => get_vector_2(lua, other)? * this,
+            (_, Some(other)) => get_vector_2(lua, this)? * other,
+            _ => {
+                let this = get_vector_2(lua, this)?;
+                let other = get_vector_2(lua, other)?;
+
+                Vector2::new(this.x * other.x, this.y * other.y)
+            }
+        };
+
+        Ok(Self { inner })
+    }
+
+    #[method(
+        from = "Vector2",
+        info = "Divide the vector by a number, or by another vector (per component). The number may be on either side.",
+        operator = "div",
+        parameter(
+            name = "other",
+            info = "Number, or other vector.",
+            kind(union(kind = "number", kind = "Vector2"))
+        ),
+        result(
+            name = "vector",
+            info = "Vector quotient.",
+            kind(user_data(name = "Vector2"))
+        )
+    )]
+    fn div(lua: &mlua::Lua, this: mlua::Value, other: mlua::Value) -> mlua::Result<Self> {
+        let inner = match (get_number(&this), get_number(&other)) {
+            (Some(this), _) => {
+                let other = get_vector_2(lua, other)?;
+
+                Vector2::new(this / other.x, this / other.y)
+            }
+            (_, Some(other)) => {
+                let this = get_vector_2(lua, this)?;
+
+                Vector2::new(this.x / other, this.y / other)
+            }
+            _ => {
+                let this = get_vector_2(lua, this)?;
+                let other = get_vector_2(lua, other)?;
+
+                Vector2::new(this.x / other.x, this.y / other.y)
+            }
+        };
+
+        Ok(Self { inner })
+    }
+
+    #[method(
+        from = "Vector2",
+        info = "Check if two vectors are equal.",
+        operator = "eq",
+        parameter(name = "other", info = "Other vector.", kind = "Vector2"),
+        result(name = "equal", info = "True if equal.", kind = "boolean")
+    )]
+    fn eq(_: &mlua::Lua, this: &Self, other: Self) -> mlua::Result<bool> {
+        Ok(this.inner.x == other.inner.x && this.inner.y == other.inner.y)
+    }
+
+    #[method(
+        from = "Vector2",
+        info = "Get the vector as a string.",
+        operator = "tostring",
+        result(name = "text", info = "Vector2, as a string.", kind = "string")
+    )]
+    fn to_string(_: &mlua::Lua, this: &Self, _: ()) -> mlua::Result<String> {
+        Ok(format!("Vector2({}, {})", this.inner.x, this.inner.y))
+    }
+
+    #[method(
+        from = "Vector2",
+        info = "Get a component (\"x\" or \"y\") of the vector.",
+        operator = "index",
+        parameter(name = "key", info = "Component name.", kind = "string"),
+        result(
+            name = "value",
+            info = "Component value.",
+            kind = "number",
+            optional = true
+        )
+    )]
+    fn index(_: &mlua::Lua, this: &Self, key: String) -> mlua::Result<Option<f32>> {
+        Ok(match key.as_str() {
+            "x" => Some(this.inner.x),
+            "y" => Some(this.inner.y),
+            _ => None,
+        })
+    }
+
+    #[method(
+        from = "Vector2",
+        info = "Set a component (\"x\" or \"y\") of the vector.",
+        operator = "newindex",
+        parameter(name = "key", info = "Component name.", kind = "string"),
+        parameter(name = "value", info = "Component value.", kind = "number")
+    )]
+    fn new_index(_: &mlua::Lua, this: &mut Self, (key, value): (String, f32)) -> mlua::Result<()> {
+        match key.as_str() {
+            "x" => this.inner.x = value,
+            "y" => this.inner.y = value,
+            _ => {
+                return Err(mlua::Error::runtime(format!(
+                    "Vector2: no component \"{key}\"."
+                )));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+//================================================================
+
+// Get a number as a float, from either a Lua integer or a Lua float ("Value::as_f32" only takes a float).
+fn get_number(value: &mlua::Value) -> Option<f32> {
+    match value {
+        mlua::Value::Integer(value) => Some(*value as f32),
+        mlua::Value::Number(value) => Some(*value as f32),
+        _ => None,
+    }
+}
+
+//================================================================
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn get_vector(code: &str) -> Vector2 {
+        let lua = mlua::Lua::new();
+        set_global(&lua, &lua.globals()).expect("Error setting module.");
+
+        lua.load(code)
+            .eval::<NativeVector2>()
+            .expect("Error running code.")
+            .inner
+    }
+
+    #[test]
+    fn mul() {
+        assert_eq!(
+            get_vector("return vector2.new(1, 2) * 2"),
+            Vector2::new(2.0, 4.0)
+        );
+        assert_eq!(
+            get_vector("return 2 * vector2.new(1, 2)"),
+            Vector2::new(2.0, 4.0)
+        );
+        assert_eq!(
+            get_vector("return vector2.new(1, 2) * 0.5"),
+            Vector2::new(0.5, 1.0)
+        );
+        assert_eq!(
+            get_vector("return vector2.new(1, 2) * vector2.new(3, 4)"),
+            Vector2::new(3.0, 8.0)
+        );
+    }
+
+    #[test]
+    fn div() {
+        assert_eq!(
+            get_vector("return vector2.new(2, 4) / 2"),
+            Vector2::new(1.0, 2.0)
+        );
+        assert_eq!(
+            get_vector("return 4 / vector2.new(2, 4)"),
+            Vector2::new(2.0, 1.0)
+        );
+        assert_eq!(
+            get_vector("return vector2.new(3, 8) / vector2.new(3, 4)"),
+            Vector2::new(1.0, 2.0)
+        );
+    }
+
+    #[test]
+    fn add_sub() {
+        assert_eq!(
+            get_vector("return vector2.new(1, 2) + vector2.new(3, 4)"),
+            Vector2::new(4.0, 6.0)
+        );
+        assert_eq!(
+            get_vector("return vector2.new(1, 2) - { x = 3, y = 4 }"),
+            Vector2::new(-2.0, -2.0)
+        );
+    }
+
+    #[test]
+    fn normalize() {
+        assert_eq!(
+            get_vector("return vector2.new(3, 4):normalize()"),
+            Vector2::new(0.6, 0.8)
+        );
+        assert_eq!(
+            get_vector("return vector2.new(0, 0):normalize()"),
+            Vector2::new(0.0, 0.0)
+        );
+    }
+
+    #[test]
+    fn rotate() {
+        let vector = get_vector("return vector2.new(1, 0):rotate(90)");
+
+        assert!((vector.x - 0.0).abs() < 1e-6);
+        assert!((vector.y - 1.0).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,660 @@
+use crate::module::archive::*;
+use crate::module::data::*;
+use crate::module::general::*;
+use crate::signature::*;
+use engine_macro::*;
+
+//================================================================
+
+use mlua::prelude::*;
+use std::io::{Cursor, Read, Seek};
+use std::path::{Component, Path, PathBuf};
+use std::time::SystemTime;
+
+//================================================================
+
+#[rustfmt::skip]
+#[module(name = "vfs", info = "Virtual file system API. Every loader (texture, font, sound, music and require) will look for a file in every mount first, from the highest priority mount to the lowest, and then on disk.")]
+pub fn set_global(lua: &mlua::Lua, global: &mlua::Table) -> anyhow::Result<()> {
+    let vfs = lua.create_table()?;
+
+    Export::set_table(lua, "vfs", &vfs)?;
+
+    global.set("vfs", vfs)?;
+
+    Ok(())
+}
+
+//================================================================
+
+// A file found through the virtual file system.
+pub enum VfsFile {
+    // File on disk, from a folder mount or from no mount at all.
+    Path(String),
+    // File in an archive, along with its extension (such as ".png").
+    Data(Vec<u8>, String),
+}
+
+impl VfsFile {
+    pub fn get_data(self) -> mlua::Result<Vec<u8>> {
+        match self {
+            Self::Path(path) => Ok(std::fs::read(path)?),
+            Self::Data(data, _) => Ok(data),
+        }
+    }
+}
+
+enum MountSource {
+    Folder(PathBuf),
+    // ZIP archive on disk, re-opened whenever it is modified, so that a hot-reload will pick up a re-built archive.
+    Archive {
+        path: PathBuf,
+        time: Option<SystemTime>,
+        inner: zip::ZipArchive<std::fs::File>,
+    },
+    // ZIP archive on disk, checked against its signature on every (re-)open. Kept in memory, so that the data
+    // checked is the data read.
+    Signed {
+        path: PathBuf,
+        time: Option<SystemTime>,
+        inner: zip::ZipArchive<Cursor<Vec<u8>>>,
+    },
+    Memory(zip::ZipArchive<Cursor<Vec<u8>>>),
+}
+
+struct Mount {
+    name: String,
+    // Folder in the virtual file system the mount is placed at, without any leading or trailing slash.
+    point: String,
+    priority: i32,
+    source: MountSource,
+    // Allow loading a LuaJIT byte-code module. Only the "main" archive is trusted with byte-code, as malformed
+    // byte-code can break out of the VM.
+    code: bool,
+}
+
+impl Mount {
+    fn find(&mut self, path: &str, key: Option<&str>) -> mlua::Result<Option<VfsFile>> {
+        let relative = if self.point.is_empty() {
+            path
+        } else if let Some(relative) = path.strip_prefix(&format!("{}/", self.point)) {
+            relative
+        } else {
+            return Ok(None);
+        };
+
+        match &mut self.source {
+            MountSource::Folder(folder) => {
+                // Do not allow leaving the folder.
+                let normal = Path::new(relative)
+                    .components()
+                    .all(|component| matches!(component, Component::Normal(_) | Component::CurDir));
+                let path = folder.join(relative);
+
+                if normal && path.is_file() {
+                    Ok(Some(VfsFile::Path(path.display().to_string())))
+                } else {
+                    Ok(None)
+                }
+            }
+            MountSource::Archive { path, time, inner } => {
+                let modified = Vfs::get_time(path);
+
+                if modified != *time {
+                    *inner = map_error(zip::ZipArchive::new(std::fs::File::open(&*path)?))?;
+                    *time = modified;
+                }
+
+                Self::find_entry(inner, relative, key)
+            }
+            MountSource::Signed { path, time, inner } => {
+                let modified = Vfs::get_time(path);
+
+                // A modified archive that does not match its signature is an error, rather than a missing file.
+                if modified != *time {
+                    let data = Signature::read(path).map_err(mlua::Error::external)?;
+                    *inner = map_error(zip::ZipArchive::new(Cursor::new(data)))?;
+                    *time = modified;
+                }
+
+                Self::find_entry(inner, relative, key)
+            }
+            MountSource::Memory(inner) => Self::find_entry(inner, relative, key),
+        }
+    }
+
+    fn find_entry<R: Read + Seek>(
+        archive: &mut zip::ZipArchive<R>,
+        path: &str,
+        key: Option<&str>,
+    ) -> mlua::Result<Option<VfsFile>> {
+        let mut entry = match get_entry(archive, path, key) {
+            Ok(entry) => entry,
+            Err(zip::result::ZipError::FileNotFound) => return Ok(None),
+            Err(error) => return Err(mlua::Error::external(error)),
+        };
+
+        if !entry.is_file() {
+            return Ok(None);
+        }
+
+        let mut data = Vec::new();
+        entry.read_to_end(&mut data)?;
+
+        let extension = Path::new(path)
+            .extension()
+            .map(|extension| format!(".{}", extension.to_string_lossy()))
+            .unwrap_or_default();
+
+        Ok(Some(VfsFile::Data(data, extension)))
+    }
+}
+
+// Every mount, stored as app data.
+#[derive(Default)]
+pub struct Vfs {
+    list: Vec<Mount>,
+}
+
+impl Vfs {
+    // Mount the "main" archive at the lowest priority, so that any other mount can patch it. If a signature is
+    // required, the archive is checked against it right away, before any script in it is run.
+    pub fn mount_main(&mut self, path: &str, point: &str) -> anyhow::Result<()> {
+        let source = if Signature::is_required() {
+            let time = Self::get_time(Path::new(path));
+            let inner = zip::ZipArchive::new(Cursor::new(Signature::read(Path::new(path))?))?;
+
+            MountSource::Signed {
+                path: PathBuf::from(path),
+                time,
+                inner,
+            }
+        } else {
+            Self::get_archive(Path::new(path))?
+        };
+
+        Ok(self.insert(path, point, i32::MIN, source, true)?)
+    }
+
+    // Mount a folder at the lowest priority, in place of the "main" archive.
+    pub fn mount_folder(&mut self, path: &str, point: &str) -> anyhow::Result<()> {
+        let source = MountSource::Folder(PathBuf::from(path));
+
+        Ok(self.insert(path, point, i32::MIN, source, false)?)
+    }
+
+    // Allow or deny byte-code in every mount. Byte-code is never allowed in safe mode.
+    pub fn set_code(&mut self, code: bool) {
+        for mount in &mut self.list {
+            mount.code = code;
+        }
+    }
+
+    // Find a file in every mount. If no mount has the file, it will be loaded from disk as is.
+    pub fn get_file(lua: &mlua::Lua, path: &str) -> mlua::Result<VfsFile> {
+        Ok(Self::find(lua, path)?.unwrap_or_else(|| VfsFile::Path(path.to_string())))
+    }
+
+    // Find a file in every mount only.
+    pub fn find(lua: &mlua::Lua, path: &str) -> mlua::Result<Option<VfsFile>> {
+        Ok(Self::find_mount(lua, path)?.map(|(file, _)| file))
+    }
+
+    // Find a file in every mount only, along with whether or not the mount allows byte-code.
+    fn find_mount(lua: &mlua::Lua, path: &str) -> mlua::Result<Option<(VfsFile, bool)>> {
+        let key = ArchiveKey::get(lua);
+
+        let Some(mut vfs) = lua.app_data_mut::<Self>() else {
+            return Ok(None);
+        };
+
+        vfs.find_file(path, key.as_deref())
+    }
+
+    // Find a file in every mount, from the highest priority mount to the lowest.
+    pub fn find_file(
+        &mut self,
+        path: &str,
+        key: Option<&str>,
+    ) -> mlua::Result<Option<(VfsFile, bool)>> {
+        let path = path.replace("\\", "/");
+        let path = path.trim_start_matches("./");
+
+        for mount in &mut self.list {
+            if let Some(file) = mount.find(path, key)? {
+                return Ok(Some((file, mount.code)));
+            }
+        }
+
+        Ok(None)
+    }
+
+    // Loader for "require", placed before the "package.path" loader so that a mount can patch a module on disk.
+    pub fn set_loader(lua: &mlua::Lua) -> mlua::Result<()> {
+        let loader: mlua::Table = lua
+            .globals()
+            .get::<mlua::Table>("package")?
+            .get("loaders")?;
+
+        for i in (2..=loader.raw_len()).rev() {
+            loader.raw_set(i + 1, loader.raw_get::<mlua::Value>(i)?)?;
+        }
+
+        loader.raw_set(
+            2,
+            lua.create_function(|lua, name: String| {
+                let path = format!("{}.lua", name.replace(".", "/"));
+
+                let Some((file, code)) = Self::find_mount(lua, &path)? else {
+                    return Ok(mlua::Value::String(lua.create_string(format!(
+                        "\n\tno file \"{path}\" in the virtual file system"
+                    ))?));
+                };
+
+                // Name the chunk after its path, so that a debugger can match it to a file on disk. A byte-code chunk
+                // (written by "engine pack") keeps the name it was compiled with.
+                let chunk = lua.load(file.get_data()?).set_name(format!("@{path}"));
+                let chunk = if code {
+                    chunk
+                } else {
+                    chunk.set_mode(mlua::ChunkMode::Text)
+                };
+
+                Ok(mlua::Value::Function(chunk.into_function()?))
+            })?,
+        )
+    }
+
+    fn mount_source(
+        lua: &mlua::Lua,
+        name: &str,
+        point: &str,
+        priority: i32,
+        source: MountSource,
+        code: bool,
+    ) -> mlua::Result<()> {
+        let Some(mut vfs) = lua.app_data_mut::<Self>() else {
+            return Err(mlua::Error::runtime(
+                "vfs.mount(): Virtual file system is not available.",
+            ));
+        };
+
+        vfs.insert(name, point, priority, source, code)
+    }
+
+    fn insert(
+        &mut self,
+        name: &str,
+        point: &str,
+        priority: i32,
+        source: MountSource,
+        code: bool,
+    ) -> mlua::Result<()> {
+        if self.list.iter().any(|mount| mount.name == name) {
+            return Err(mlua::Error::runtime(format!(
+                "vfs.mount(): \"{name}\" is already mounted."
+            )));
+        }
+
+        // Keep the list sorted from the highest priority to the lowest. Among mounts of the same priority, the
+        // latest mount takes priority.
+        let index = self
+            .list
+            .iter()
+            .position(|mount| mount.priority <= priority)
+            .unwrap_or(self.list.len());
+
+        self.list.insert(
+            index,
+            Mount {
+                name: name.to_string(),
+                point: point.trim_matches('/').to_string(),
+                priority,
+                source,
+                code,
+            },
+        );
+
+        Ok(())
+    }
+
+    fn get_time(path: &Path) -> Option<SystemTime> {
+        std::fs::metadata(path)
+            .and_then(|file| file.modified())
+            .ok()
+    }
+
+    fn get_archive(path: &Path) -> mlua::Result<MountSource> {
+        let time = Self::get_time(path);
+        let inner = map_error(zip::ZipArchive::new(std::fs::File::open(path)?))?;
+
+        Ok(MountSource::Archive {
+            path: path.to_path_buf(),
+            time,
+            inner,
+        })
+    }
+}
+
+//================================================================
+
+#[function(
+    from = "vfs",
+    info = "Mount a folder, or a ZIP archive.",
+    parameter(
+        name = "path",
+        info = "Path to folder or archive. Also used as the mount name.",
+        kind = "string"
+    ),
+    parameter(
+        name = "point",
+        info = "Folder to mount at, such as \"main\". Default: root.",
+        kind = "string",
+        optional = true
+    ),
+    parameter(
+        name = "priority",
+        info = "Mount priority. A higher priority mount will take over any file in a lower priority mount. Default: 0.",
+        kind = "number",
+        optional = true
+    )
+)]
+fn mount(
+    lua: &mlua::Lua,
+    (path, point, priority): (String, Option<String>, Option<i32>),
+) -> mlua::Result<()> {
+    let full = DataRoot::get_path(lua, &path)?;
+
+    let source = if full.is_dir() {
+        MountSource::Folder(full)
+    } else {
+        Vfs::get_archive(&full)?
+    };
+
+    Vfs::mount_source(
+        lua,
+        &path,
+        &point.unwrap_or_default(),
+        priority.unwrap_or_default(),
+        source,
+        false,
+    )
+}
+
+#[function(
+    from = "vfs",
+    info = "Mount a ZIP archive from memory.",
+    parameter(name = "name", info = "Mount name.", kind = "string"),
+    parameter(
+        name = "data",
+        info = "Archive data, as a string, or as a table array of every byte.",
+        kind(union(kind = "string", kind = "table"))
+    ),
+    parameter(
+        name = "point",
+        info = "Folder to mount at, such as \"main\". Default: root.",
+        kind = "string",
+        optional = true
+    ),
+    parameter(
+        name = "priority",
+        info = "Mount priority. A higher priority mount will take over any file in a lower priority mount. Default: 0.",
+        kind = "number",
+        optional = true
+    )
+)]
+fn mount_data(
+    lua: &mlua::Lua,
+    (name, data, point, priority): (String, mlua::Value, Option<String>, Option<i32>),
+) -> mlua::Result<()> {
+    let data = match data {
+        mlua::Value::String(data) => data.as_bytes().to_vec(),
+        data => Vec::<u8>::from_lua(data, lua)?,
+    };
+    let inner = map_error(zip::ZipArchive::new(Cursor::new(data)))?;
+
+    Vfs::mount_source(
+        lua,
+        &name,
+        &point.unwrap_or_default(),
+        priority.unwrap_or_default(),
+        MountSource::Memory(inner),
+        false,
+    )
+}
+
+#[function(
+    from = "vfs",
+    info = "Remove a mount.",
+    parameter(name = "name", info = "Mount name.", kind = "string"),
+    result(
+        name = "remove",
+        info = "True if the mount was removed.",
+        kind = "boolean"
+    )
+)]
+fn unmount(lua: &mlua::Lua, name: String) -> mlua::Result<bool> {
+    let Some(mut vfs) = lua.app_data_mut::<Vfs>() else {
+        return Ok(false);
+    };
+
+    let count = vfs.list.len();
+    vfs.list.retain(|mount| mount.name != name);
+
+    Ok(vfs.list.len() != count)
+}
+
+#[function(
+    from = "vfs",
+    info = "Get a list of every mount, from the highest priority to the lowest.",
+    result(
+        name = "mount_list",
+        info = "Table array of every mount name.",
+        kind = "table"
+    )
+)]
+fn get_mount_list(lua: &mlua::Lua, _: ()) -> mlua::Result<Vec<String>> {
+    let Some(vfs) = lua.app_data_ref::<Vfs>() else {
+        return Ok(Vec::new());
+    };
+
+    Ok(vfs.list.iter().map(|mount| mount.name.clone()).collect())
+}
+
+#[function(
+    from = "vfs",
+    info = "Check if a file is in any mount.",
+    parameter(name = "path", info = "Path to file.", kind = "string"),
+    result(name = "find", info = "True if found.", kind = "boolean")
+)]
+fn has_file(lua: &mlua::Lua, path: String) -> mlua::Result<bool> {
+    Ok(Vfs::find(lua, &path)?.is_some())
+}
+
+#[function(
+    from = "vfs",
+    info = "Get the data of a file, from the highest priority mount with the file, or from disk.",
+    parameter(name = "path", info = "Path to file.", kind = "string"),
+    parameter(
+        name = "binary",
+        info = "Return the value as binary, or as a string.",
+        kind = "boolean"
+    ),
+    result(
+        name = "data",
+        info = "File data.",
+        kind(union(kind = "table", kind = "string"))
+    ),
+    overload(
+        parameter(name = "path", kind = "string"),
+        parameter(name = "binary", kind(literal(value = "true"))),
+        result(name = "data", kind = "table")
+    ),
+    overload(
+        parameter(name = "path", kind = "string"),
+        parameter(name = "binary", kind(literal(value = "false"))),
+        result(name = "data", kind = "string")
+    )
+)]
+fn get_file(lua: &mlua::Lua, (path, binary): (String, bool)) -> mlua::Result<mlua::Value> {
+    let data = match Vfs::get_file(lua, &path)? {
+        VfsFile::Path(path) => std::fs::read(DataRoot::get_path(lua, &path)?)?,
+        VfsFile::Data(data, _) => data,
+    };
+
+    if binary {
+        Ok(lua.to_value(&data)?)
+    } else {
+        Ok(lua.to_value(&String::from_utf8(data).map_err(mlua::Error::external)?)?)
+    }
+}
+
+//================================================================
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::io::Write;
+
+    // Create a ZIP archive in memory, with every (path, data) entry in it.
+    fn get_archive(list: &[(&str, &str)]) -> Vec<u8> {
+        let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+
+        for (path, data) in list {
+            writer
+                .start_file(*path, zip::write::SimpleFileOptions::default())
+                .expect("Error starting file.");
+            writer
+                .write_all(data.as_bytes())
+                .expect("Error writing file.");
+        }
+
+        writer
+            .finish()
+            .expect("Error finishing archive.")
+            .into_inner()
+    }
+
+    fn get_source(list: &[(&str, &str)]) -> MountSource {
+        MountSource::Memory(
+            zip::ZipArchive::new(Cursor::new(get_archive(list))).expect("Error reading archive."),
+        )
+    }
+
+    // Get the data of a file, from the mount with the highest priority.
+    fn get_data(vfs: &mut Vfs, path: &str) -> Option<String> {
+        let (file, _) = vfs.find_file(path, None).expect("Error finding file.")?;
+
+        Some(String::from_utf8(file.get_data().expect("Error reading file.")).unwrap())
+    }
+
+    #[test]
+    fn priority() {
+        let mut vfs = Vfs::default();
+
+        vfs.insert(
+            "base",
+            "",
+            0,
+            get_source(&[("a.txt", "base"), ("b.txt", "base")]),
+            false,
+        )
+        .unwrap();
+        vfs.insert("high", "", 1, get_source(&[("a.txt", "high")]), false)
+            .unwrap();
+        vfs.insert(
+            "low",
+            "",
+            -1,
+            get_source(&[("a.txt", "low"), ("c.txt", "low")]),
+            false,
+        )
+        .unwrap();
+
+        assert_eq!(get_data(&mut vfs, "a.txt").as_deref(), Some("high"));
+        assert_eq!(get_data(&mut vfs, "./b.txt").as_deref(), Some("base"));
+        assert_eq!(get_data(&mut vfs, "c.txt").as_deref(), Some("low"));
+        assert_eq!(get_data(&mut vfs, "d.txt"), None);
+
+        // Among mounts of the same priority, the latest mount takes priority.
+        vfs.insert("late", "", 0, get_source(&[("b.txt", "late")]), false)
+            .unwrap();
+
+        assert_eq!(get_data(&mut vfs, "b.txt").as_deref(), Some("late"));
+
+        let name: Vec<&str> = vfs.list.iter().map(|mount| mount.name.as_str()).collect();
+        assert_eq!(name, ["high", "late", "base", "low"]);
+
+        // A name can only be mounted once.
+        assert!(vfs.insert("base", "", 0, get_source(&[]), false).is_err());
+    }
+
+    #[test]
+    fn point() {
+        let mut vfs = Vfs::default();
+
+        vfs.insert(
+            "main",
+            "/main/",
+            i32::MIN,
+            get_source(&[("main.lua", "main")]),
+            true,
+        )
+        .unwrap();
+        vfs.insert(
+            "patch",
+            "",
+            0,
+            get_source(&[("main/main.lua", "patch")]),
+            false,
+        )
+        .unwrap();
+
+        // A mount at a folder only has files within that folder.
+        assert_eq!(get_data(&mut vfs, "main.lua"), None);
+        assert_eq!(
+            get_data(&mut vfs, "main\\main.lua").as_deref(),
+            Some("patch")
+        );
+
+        // Byte-code is only allowed in a trusted mount, until denied for every mount.
+        vfs.list.retain(|mount| mount.name != "patch");
+
+        assert!(matches!(
+            vfs.find_file("main/main.lua", None),
+            Ok(Some((_, true)))
+        ));
+        vfs.set_code(false);
+        assert!(matches!(
+            vfs.find_file("main/main.lua", None),
+            Ok(Some((_, false)))
+        ));
+    }
+
+    #[test]
+    fn mount_data() {
+        let lua = mlua::Lua::new();
+        lua.set_app_data(Vfs::default());
+        set_global(&lua, &lua.globals()).expect("Error setting module.");
+
+        let data = lua
+            .create_string(get_archive(&[("a.txt", "data")]))
+            .unwrap();
+        lua.globals().set("data", data).unwrap();
+
+        lua.load(
+            r#"
+            vfs.mount_data("first", data)
+            vfs.mount_data("second", data, "", 1)
+            assert(vfs.get_mount_list()[1] == "second")
+            assert(vfs.has_file("a.txt"))
+            assert(vfs.get_file("a.txt", false) == "data")
+            assert(vfs.unmount("second"))
+            assert(vfs.unmount("first"))
+            assert(not vfs.has_file("a.txt"))
+            "#,
+        )
+        .exec()
+        .expect("Error running code.");
+    }
+}
@@ -1,3 +1,4 @@
+use crate::module::general::*;
 use engine_macro::*;
 
 //================================================================
@@ -12,32 +13,30 @@ use raylib::prelude::*;
 pub fn set_global(lua: &mlua::Lua, global: &mlua::Table) -> anyhow::Result<()> {
     let window = lua.create_table()?;
 
-    window.set("is_exit",            lua.create_function(self::is_exit)?)?;
-    window.set("is_full_screen",     lua.create_function(self::is_full_screen)?)?;
-    window.set("toggle_full_screen", lua.create_function(self::toggle_full_screen)?)?;
-    window.set("set_exit_key",       lua.create_function(self::set_exit_key)?)?;
-    window.set("get_screen_scale",   lua.create_function(self::get_screen_scale)?)?;
-    window.set("get_window_scale",   lua.create_function(self::get_window_scale)?)?;
-    window.set("set_window_scale",   lua.create_function(self::set_window_scale)?)?;
-    window.set("get_render_scale",   lua.create_function(self::get_render_scale)?)?;
-    window.set("set_frame_sync",     lua.create_function(self::set_frame_sync)?)?;
-    window.set("set_frame_rate",     lua.create_function(self::set_frame_rate)?)?;
-    window.set("get_frame_time",     lua.create_function(self::get_frame_time)?)?;
-    window.set("get_time",           lua.create_function(self::get_time)?)?;
-    window.set("get_frame_rate",     lua.create_function(self::get_frame_rate)?)?;
-    window.set("is_focus",           lua.create_function(self::is_focus)?)?;
-    window.set("is_scale_new",       lua.create_function(self::is_scale_new)?)?;
-    window.set("dialog_message",     lua.create_function(self::dialog_message)?)?;
-    //window.set("dialog_pick_file",   lua.create_function(self::dialog_pick_file)?)?;
-    //window.set("dialog_pick_path",   lua.create_function(self::dialog_pick_path)?)?;
+    Export::set_table(lua, "window", &window)?;
 
     global.set("window", window)?;
 
+    MessageKind::set_global(lua, global)?;
+
     Ok(())
 }
 
 //================================================================
 
+#[enumeration(info = "Message kind.")]
+#[derive(Clone, Copy)]
+pub enum MessageKind {
+    /// Information message.
+    Info,
+    /// Warning message.
+    Warning,
+    /// Error message.
+    Error,
+}
+
+//================================================================
+
 #[function(
     from = "window",
     info = "Get the exit state of the window.",
@@ -121,7 +120,7 @@ fn get_window_scale(lua: &mlua::Lua, _: ()) -> mlua::Result<mlua::Value> {
 )]
 fn set_window_scale(lua: &mlua::Lua, scale: mlua::Value) -> mlua::Result<()> {
     unsafe {
-        let value: Vector2 = lua.from_value(scale)?;
+        let value = get_vector_2(lua, scale)?;
         ffi::SetWindowSize(value.x as i32, value.y as i32);
         Ok(())
     }
@@ -237,12 +236,12 @@ fn is_scale_new(_: &mlua::Lua, _: ()) -> mlua::Result<bool> {
 )]
 fn dialog_message(
     _: &mlua::Lua,
-    (kind, name, text): (usize, String, String),
+    (kind, name, text): (MessageKind, String, String),
 ) -> mlua::Result<bool> {
     let kind = match kind {
-        0 => rfd::MessageLevel::Info,
-        1 => rfd::MessageLevel::Warning,
-        _ => rfd::MessageLevel::Error,
+        MessageKind::Info => rfd::MessageLevel::Info,
+        MessageKind::Warning => rfd::MessageLevel::Warning,
+        MessageKind::Error => rfd::MessageLevel::Error,
     };
 
     let result = rfd::MessageDialog::new()
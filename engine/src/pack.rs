@@ -0,0 +1,405 @@
+use crate::argument::*;
+use crate::signature::*;
+
+//================================================================
+
+use sha2::Digest;
+use std::io::{Seek, Write};
+use std::path::{Path, PathBuf};
+
+//================================================================
+
+const HELP: &str = r##"Usage: engine pack [OPTION]... [FOLDER]
+
+Pack the "main" folder of a game folder (default: the current folder) into a
+"main" ZIP archive, with every Lua file compiled to LuaJIT byte-code, and write
+a manifest with the hash of every file. Any path matching a pattern in the
+"main/.packignore" file is left out.
+
+Every line in the ignore file is a pattern, such as "*.blend" or "test/". A
+pattern with no slash matches a file or folder with that name at any depth,
+and a pattern with a slash matches from the "main" folder. A pattern ending
+with a slash only matches a folder. "*" matches anything but a slash, "**"
+matches anything, and "?" matches any character but a slash. Blank lines and
+lines starting with "#" are skipped.
+
+Option:
+    --output FOLDER   Folder to write to. Default: "pack".
+    --embed           Embed the archive into a copy of the engine executable,
+                      rather than writing it on its own.
+    --source          Pack every Lua file as source, rather than as byte-code.
+    --strip           Strip the debug information from the byte-code.
+    --sign FILE       Sign the archive (or the executable, if embedded), with
+                      the hex-encoded Ed25519 secret key (32 byte) in FILE.
+    --help            Show this message."##;
+
+// A pattern from the ignore file.
+struct Ignore {
+    pattern: String,
+    // Only match a folder.
+    folder: bool,
+    // Match from the "main" folder, rather than the name at any depth.
+    anchor: bool,
+}
+
+impl Ignore {
+    fn new(line: &str) -> Option<Self> {
+        let line = line.trim();
+
+        if line.is_empty() || line.starts_with('#') {
+            return None;
+        }
+
+        let folder = line.ends_with('/');
+        let line = line.trim_end_matches('/');
+        let anchor = line.contains('/');
+
+        Some(Self {
+            pattern: line.trim_start_matches('/').to_string(),
+            folder,
+            anchor,
+        })
+    }
+
+    fn is_match(&self, path: &str, folder: bool) -> bool {
+        if self.folder && !folder {
+            return false;
+        }
+
+        if self.anchor {
+            Self::is_glob(self.pattern.as_bytes(), path.as_bytes())
+        } else {
+            let name = path.rsplit('/').next().unwrap_or(path);
+
+            Self::is_glob(self.pattern.as_bytes(), name.as_bytes())
+        }
+    }
+
+    fn is_glob(pattern: &[u8], text: &[u8]) -> bool {
+        match pattern {
+            [] => text.is_empty(),
+            [b'*', b'*', rest @ ..] => (0..=text.len()).any(|i| Self::is_glob(rest, &text[i..])),
+            [b'*', rest @ ..] => (0..=text.len())
+                .take_while(|i| *i == 0 || text[i - 1] != b'/')
+                .any(|i| Self::is_glob(rest, &text[i..])),
+            [b'?', rest @ ..] => {
+                matches!(text, [character, ..] if *character != b'/')
+                    && Self::is_glob(rest, &text[1..])
+            }
+            [character, rest @ ..] => {
+                text.first() == Some(character) && Self::is_glob(rest, &text[1..])
+            }
+        }
+    }
+}
+
+pub struct Pack {
+    path: PathBuf,
+    output: PathBuf,
+    embed: bool,
+    source: bool,
+    strip: bool,
+    sign: Option<String>,
+}
+
+impl Pack {
+    pub const COMMAND: &str = "pack";
+    const OUTPUT_PATH: &str = "pack";
+    const IGNORE_FILE: &str = ".packignore";
+    const MANIFEST_FILE: &str = "manifest.json";
+
+    // Run the "pack" command, with every argument after the command itself.
+    pub fn run(mut list: impl Iterator<Item = String>) -> anyhow::Result<()> {
+        let mut pack = Self {
+            path: PathBuf::from("."),
+            output: PathBuf::from(Self::OUTPUT_PATH),
+            embed: false,
+            source: false,
+            strip: false,
+            sign: None,
+        };
+
+        while let Some(token) = list.next() {
+            match token.as_str() {
+                "--output" => pack.output = PathBuf::from(Self::get_value(&mut list, &token)?),
+                "--embed" => pack.embed = true,
+                "--source" => pack.source = true,
+                "--strip" => pack.strip = true,
+                "--sign" => pack.sign = Some(Self::get_value(&mut list, &token)?),
+                "--help" => {
+                    println!("{HELP}");
+                    return Ok(());
+                }
+                _ => {
+                    if token.starts_with("--") {
+                        return Err(anyhow::anyhow!(
+                            "Unknown option \"{token}\". Use \"pack --help\" for a list of every option."
+                        ));
+                    }
+
+                    pack.path = PathBuf::from(token);
+                }
+            }
+        }
+
+        pack.write()
+    }
+
+    // Get the path to the engine executable, if it has a "main" ZIP archive embedded into it.
+    pub fn get_embed() -> Option<String> {
+        let path = std::env::current_exe().ok()?;
+        let file = std::fs::File::open(&path).ok()?;
+
+        zip::ZipArchive::new(file).ok()?;
+
+        Some(path.display().to_string())
+    }
+
+    fn write(&self) -> anyhow::Result<()> {
+        let main = self.path.join(crate::Script::MAIN_FOLDER);
+
+        if !main.is_dir() {
+            return Err(anyhow::anyhow!(
+                "No \"{}\" folder in \"{}\".",
+                crate::Script::MAIN_FOLDER,
+                self.path.display()
+            ));
+        }
+
+        // A missing ignore file has no pattern, but any other error (such as an unreadable file) is not skipped.
+        let ignore = match std::fs::read_to_string(main.join(Self::IGNORE_FILE)) {
+            Ok(ignore) => ignore,
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(error) => {
+                return Err(anyhow::anyhow!(
+                    "Error reading \"{}\": {error}",
+                    Self::IGNORE_FILE
+                ));
+            }
+        };
+        let ignore: Vec<Ignore> = ignore.lines().filter_map(Ignore::new).collect();
+
+        let mut file_list = Vec::new();
+        Self::get_file_list(&mut file_list, &main, "", &ignore)?;
+
+        //================================================================
+
+        // Either write the archive right after a copy of the executable, or on its own. The archive is written
+        // after the executable data, rather than appended once written, so that every offset in it is correct.
+        let (path, data) = if self.embed {
+            let engine = std::env::current_exe()?;
+            let name = engine.file_name().unwrap_or_default();
+
+            (self.output.join(name), std::fs::read(&engine)?)
+        } else {
+            (self.output.join(Argument::MAIN_FILE), Vec::new())
+        };
+
+        let mut data = std::io::Cursor::new(data);
+        data.seek(std::io::SeekFrom::End(0))?;
+
+        // Write the archive. Every entry has a fixed time, and is written in the same order, so that packing the
+        // same files twice gives the same archive.
+        let lua = mlua::Lua::new();
+        let mut writer = zip::ZipWriter::new(data);
+        let mut manifest = Vec::new();
+
+        for path in &file_list {
+            let mut data = std::fs::read(main.join(path))?;
+
+            if !self.source && path.ends_with(".lua") {
+                // Name the chunk after its path, so that a debugger can match it to a file on disk.
+                data = lua
+                    .load(&data)
+                    .set_name(format!("@{}/{path}", crate::Script::MAIN_FOLDER))
+                    .into_function()
+                    .map_err(|error| anyhow::anyhow!("Error compiling \"{path}\": {error}"))?
+                    .dump(self.strip);
+            }
+
+            let option = zip::write::SimpleFileOptions::default()
+                .compression_method(zip::CompressionMethod::Deflated)
+                .last_modified_time(zip::DateTime::default())
+                .large_file(data.len() as u64 >= u32::MAX as u64);
+
+            writer.start_file(path, option)?;
+            writer.write_all(&data)?;
+
+            manifest.push(Self::get_hash(
+                &format!("{}/{path}", crate::Script::MAIN_FOLDER),
+                &data,
+            ));
+        }
+
+        let data = writer.finish()?.into_inner();
+
+        //================================================================
+
+        std::fs::create_dir_all(&self.output)?;
+        std::fs::write(&path, &data)?;
+
+        // Keep the executable runnable.
+        #[cfg(unix)]
+        if self.embed {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755))?;
+        }
+
+        if let Some(sign) = &self.sign {
+            let key = std::fs::read_to_string(sign)?;
+            let (signature, public) = Signature::sign(&data, key.trim())?;
+
+            std::fs::write(Signature::get_path(&path), format!("{signature}\n"))?;
+
+            println!("Signed \"{}\". Public key: {public}", path.display());
+        }
+
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+
+        std::fs::write(
+            self.output.join(Self::MANIFEST_FILE),
+            serde_json::to_string_pretty(&serde_json::json!({
+                "version": env!("CARGO_PKG_VERSION"),
+                "archive": Self::get_hash(&name, &data),
+                "file": manifest,
+            }))?,
+        )?;
+
+        println!(
+            "Packed {} file(s) into \"{}\".",
+            file_list.len(),
+            path.display()
+        );
+
+        Ok(())
+    }
+
+    // Get every file in a folder (as a path relative to the "main" folder), sorted by path, and skipping every
+    // ignored path.
+    fn get_file_list(
+        list: &mut Vec<String>,
+        main: &Path,
+        path: &str,
+        ignore: &[Ignore],
+    ) -> anyhow::Result<()> {
+        let mut entry: Vec<_> = std::fs::read_dir(main.join(path))?.collect::<Result<_, _>>()?;
+        entry.sort_by_key(|entry| entry.file_name());
+
+        for entry in entry {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let file = if path.is_empty() {
+                name
+            } else {
+                format!("{path}/{name}")
+            };
+            let folder = entry.file_type()?.is_dir();
+
+            if file == Self::IGNORE_FILE || ignore.iter().any(|rule| rule.is_match(&file, folder)) {
+                continue;
+            }
+
+            if folder {
+                Self::get_file_list(list, main, &file, ignore)?;
+            } else {
+                list.push(file);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn get_hash(path: &str, data: &[u8]) -> serde_json::Value {
+        serde_json::json!({
+            "path": path,
+            "size": data.len(),
+            "sha256": Signature::to_hex(&sha2::Sha256::digest(data)),
+        })
+    }
+
+    fn get_value(list: &mut impl Iterator<Item = String>, name: &str) -> anyhow::Result<String> {
+        list.next()
+            .ok_or_else(|| anyhow::anyhow!("Missing value for option \"{name}\"."))
+    }
+}
+
+//================================================================
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn is_match(pattern: &str, path: &str, folder: bool) -> bool {
+        Ignore::new(pattern).unwrap().is_match(path, folder)
+    }
+
+    #[test]
+    fn is_glob() {
+        assert!(Ignore::is_glob(b"*.blend", b"model.blend"));
+        assert!(!Ignore::is_glob(b"*.blend", b"model/a.blend"));
+        assert!(Ignore::is_glob(b"**.blend", b"model/a.blend"));
+        assert!(Ignore::is_glob(b"test/**", b"test/a/b.lua"));
+        assert!(Ignore::is_glob(b"?.lua", b"a.lua"));
+        assert!(!Ignore::is_glob(b"?.lua", b"ab.lua"));
+        assert!(!Ignore::is_glob(b"a?b", b"a/b"));
+        assert!(Ignore::is_glob(b"main.lua", b"main.lua"));
+        assert!(!Ignore::is_glob(b"main.lua", b"main.luac"));
+    }
+
+    #[test]
+    fn is_match_anchor() {
+        // No slash: match the name at any depth.
+        assert!(is_match("*.blend", "model/a.blend", false));
+        assert!(is_match("test", "a/test", false));
+        // A slash: match from the "main" folder.
+        assert!(is_match("/test", "test", true));
+        assert!(!is_match("/test", "a/test", true));
+        assert!(is_match("model/*.blend", "model/a.blend", false));
+        assert!(!is_match("model/*.blend", "a/model/a.blend", false));
+    }
+
+    #[test]
+    fn is_match_folder() {
+        assert!(is_match("test/", "test", true));
+        assert!(!is_match("test/", "test", false));
+        assert!(Ignore::new("").is_none());
+        assert!(Ignore::new("  # comment").is_none());
+    }
+
+    #[test]
+    fn write_same() {
+        let path = std::env::temp_dir().join(format!("flak_pack_{}", std::process::id()));
+        let main = path.join(crate::Script::MAIN_FOLDER);
+
+        std::fs::create_dir_all(main.join("model")).unwrap();
+        std::fs::write(main.join("main.lua"), "return { value = 1 }").unwrap();
+        std::fs::write(main.join("model/a.lua"), "return 2").unwrap();
+        std::fs::write(main.join("model/a.blend"), "model").unwrap();
+        std::fs::write(main.join(Pack::IGNORE_FILE), "*.blend\n").unwrap();
+
+        let mut data = Vec::new();
+
+        for output in ["a", "b"] {
+            let pack = Pack {
+                path: path.clone(),
+                output: path.join(output),
+                embed: false,
+                source: false,
+                strip: false,
+                sign: None,
+            };
+
+            pack.write().unwrap();
+            data.push(std::fs::read(pack.output.join(Argument::MAIN_FILE)).unwrap());
+        }
+
+        assert_eq!(data[0], data[1]);
+
+        let archive = zip::ZipArchive::new(std::io::Cursor::new(&data[0])).unwrap();
+        let name: Vec<&str> = archive.file_names().collect();
+
+        assert_eq!(archive.len(), 2);
+        assert!(name.contains(&"main.lua") && name.contains(&"model/a.lua"));
+
+        std::fs::remove_dir_all(&path).unwrap();
+    }
+}
@@ -0,0 +1,226 @@
+use crate::console::*;
+
+//================================================================
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::Instant;
+use std::time::SystemTime;
+
+//================================================================
+
+// Hot-reload of every required module. Every module table is patched in place, so any function looked up from it
+// again is the new one. The "main" and "fail" entry functions are only taken again in between loop iterations, which
+// is every frame with "info.step" or without a window. A "main" function that owns the loop keeps running as is, and
+// its new version is only taken once it returns.
+pub struct Reload {
+    // Entry module, the script table is taken from here.
+    entry: String,
+    // Folder and ZIP archive to watch.
+    path: String,
+    file: String,
+    // Modification time of every Lua file in the folder.
+    path_list: HashMap<String, SystemTime>,
+    // Modification time of the ZIP archive, and CRC of every Lua file in it.
+    file_time: Option<SystemTime>,
+    file_list: HashMap<String, u32>,
+    time: Instant,
+    // Set whenever a module has been re-loaded, until taken by the engine.
+    reload: bool,
+}
+
+impl Reload {
+    const ENTRY_RELOAD: &str = "reload";
+    // Time (in seconds) in between every file system check.
+    const RATE: f32 = 0.5;
+
+    pub fn new(entry: &str, path: &str, file: &str) -> Self {
+        let mut reload = Self {
+            entry: entry.to_string(),
+            path: path.to_string(),
+            file: file.to_string(),
+            path_list: HashMap::new(),
+            file_time: None,
+            file_list: HashMap::new(),
+            time: Instant::now(),
+            reload: false,
+        };
+
+        // Take an initial snapshot, so that only later changes will trigger a reload.
+        reload.get_change();
+
+        reload
+    }
+
+    pub fn update(lua: &mlua::Lua) -> mlua::Result<()> {
+        // Borrow the watcher only for the file system check, as re-loading a module will run Lua code.
+        let (change, entry) = if let Some(mut reload) = lua.app_data_mut::<Self>() {
+            if reload.time.elapsed().as_secs_f32() < Self::RATE {
+                return Ok(());
+            }
+
+            reload.time = Instant::now();
+
+            (reload.get_change(), reload.entry.clone())
+        } else {
+            return Ok(());
+        };
+
+        if change.is_empty() {
+            return Ok(());
+        }
+
+        let loaded: mlua::Table = lua.globals().get::<mlua::Table>("package")?.get("loaded")?;
+        let mut module_list = Vec::new();
+
+        // Only re-load modules that have been required before. Modules can be required with either "/" or ".".
+        for pair in loaded.pairs::<String, mlua::Value>() {
+            let (name, _) = pair?;
+
+            if change.contains(&name.replace(".", "/")) {
+                module_list.push(name);
+            }
+        }
+
+        let mut reload_list = Vec::new();
+
+        for name in module_list {
+            if let Err(error) = Self::reload_module(lua, &loaded, &name) {
+                // The old module keeps running, so the error is only reported rather than raised.
+                let error = format!("Error re-loading module \"{name}\": {error}");
+
+                eprintln!("{error}");
+
+                if let Some(mut console) = lua.app_data_mut::<Console>() {
+                    console.push(&error);
+                }
+            } else {
+                reload_list.push(name);
+            }
+        }
+
+        if reload_list.is_empty() {
+            return Ok(());
+        }
+
+        if let Some(mut reload) = lua.app_data_mut::<Self>() {
+            reload.reload = true;
+        }
+
+        if let mlua::Value::Table(table) = loaded.get::<mlua::Value>(entry)?
+            && let Ok(call) = table.get::<mlua::Function>(Self::ENTRY_RELOAD)
+        {
+            call.call::<()>((&table, reload_list))?;
+        }
+
+        Ok(())
+    }
+
+    // Check if any module has been re-loaded since the last call, so that every entry function can be taken again
+    // from the script table.
+    pub fn take(lua: &mlua::Lua) -> bool {
+        lua.app_data_mut::<Self>()
+            .map(|mut reload| std::mem::take(&mut reload.reload))
+            .unwrap_or(false)
+    }
+
+    fn reload_module(lua: &mlua::Lua, loaded: &mlua::Table, name: &str) -> mlua::Result<()> {
+        let require: mlua::Function = lua.globals().get("require")?;
+        let old: mlua::Value = loaded.get(name)?;
+
+        loaded.set(name, mlua::Nil)?;
+
+        match require.call::<mlua::Value>(name) {
+            Ok(new) => {
+                // Patch the old module table in-place, so that every reference to it stays valid, and
+                // any state not re-defined by the new module is preserved.
+                if let (mlua::Value::Table(old), mlua::Value::Table(new)) = (&old, &new) {
+                    for pair in new.pairs::<mlua::Value, mlua::Value>() {
+                        let (key, value) = pair?;
+                        old.set(key, value)?;
+                    }
+
+                    loaded.set(name, old)?;
+                }
+
+                Ok(())
+            }
+            Err(error) => {
+                // Keep the old module running if the new one fails to load.
+                loaded.set(name, old)?;
+
+                Err(error)
+            }
+        }
+    }
+
+    fn get_change(&mut self) -> Vec<String> {
+        let mut change = Vec::new();
+
+        //================================================================
+
+        let mut path_list = HashMap::new();
+        Self::get_path_list(&mut path_list, Path::new(&self.path));
+
+        for (path, time) in &path_list {
+            if self.path_list.get(path) != Some(time) {
+                change.push(path.clone());
+            }
+        }
+
+        self.path_list = path_list;
+
+        //================================================================
+
+        let file_time = std::fs::metadata(&self.file)
+            .and_then(|file| file.modified())
+            .ok();
+
+        if file_time.is_some() && file_time != self.file_time {
+            let mut file_list = HashMap::new();
+
+            if let Ok(file) = std::fs::File::open(&self.file)
+                && let Ok(mut file) = zip::ZipArchive::new(file)
+            {
+                for i in 0..file.len() {
+                    if let Ok(entry) = file.by_index_raw(i)
+                        && let Some(name) = entry.name().strip_suffix(".lua")
+                    {
+                        file_list.insert(format!("{}/{}", self.path, name), entry.crc32());
+                    }
+                }
+            }
+
+            for (path, crc) in &file_list {
+                if self.file_list.get(path) != Some(crc) {
+                    change.push(path.clone());
+                }
+            }
+
+            self.file_list = file_list;
+        }
+
+        self.file_time = file_time;
+
+        change
+    }
+
+    fn get_path_list(list: &mut HashMap<String, SystemTime>, path: &Path) {
+        let Ok(path) = std::fs::read_dir(path) else {
+            return;
+        };
+
+        for file in path.flatten() {
+            let path = file.path();
+
+            if path.is_dir() {
+                Self::get_path_list(list, &path);
+            } else if let Some(name) = path.to_str()
+                && let Some(name) = name.strip_suffix(".lua")
+                && let Ok(time) = file.metadata().and_then(|file| file.modified())
+            {
+                list.insert(name.replace("\\", "/"), time);
+            }
+        }
+    }
+}
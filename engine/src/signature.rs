@@ -0,0 +1,166 @@
+use std::path::{Path, PathBuf};
+
+//================================================================
+
+// Detached Ed25519 signature of the "main" ZIP archive, checked before any script in it is run.
+pub struct Signature;
+
+impl Signature {
+    // Hex-encoded public key, embedded at build time. If set, the "main" ZIP archive must be signed.
+    const PUBLIC_KEY: Option<&str> = option_env!("FLAK_PUBLIC_KEY");
+    // Extension of the signature file, placed next to the archive (such as "main.sig").
+    pub const EXTENSION: &str = "sig";
+
+    pub fn is_required() -> bool {
+        Self::PUBLIC_KEY.is_some()
+    }
+
+    // Read an archive, and check it against its signature, if required. The data read is returned, so that the data
+    // checked is the data used.
+    pub fn read(path: &Path) -> anyhow::Result<Vec<u8>> {
+        let data = std::fs::read(path)?;
+
+        if let Some(key) = Self::PUBLIC_KEY {
+            Self::verify(&data, path, key)?;
+        }
+
+        Ok(data)
+    }
+
+    pub fn get_path(path: &Path) -> PathBuf {
+        let mut path = path.as_os_str().to_os_string();
+        path.push(format!(".{}", Self::EXTENSION));
+
+        PathBuf::from(path)
+    }
+
+    // Sign data with a hex-encoded secret key. Return the hex-encoded signature, and the hex-encoded public key to
+    // build the engine with.
+    pub fn sign(data: &[u8], key: &str) -> anyhow::Result<(String, String)> {
+        use ed25519_dalek::Signer;
+
+        let key: [u8; 32] = Self::get_hex(key)
+            .and_then(|key| key.try_into().ok())
+            .ok_or_else(|| anyhow::anyhow!("Invalid secret key, for archive signature."))?;
+        let key = ed25519_dalek::SigningKey::from_bytes(&key);
+
+        Ok((
+            Self::to_hex(&key.sign(data).to_bytes()),
+            Self::to_hex(key.verifying_key().as_bytes()),
+        ))
+    }
+
+    pub fn to_hex(data: &[u8]) -> String {
+        data.iter().map(|byte| format!("{byte:02x}")).collect()
+    }
+
+    pub fn get_hex(text: &str) -> Option<Vec<u8>> {
+        if !text.len().is_multiple_of(2) {
+            return None;
+        }
+
+        (0..text.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(text.get(i..i + 2)?, 16).ok())
+            .collect()
+    }
+
+    fn verify(data: &[u8], path: &Path, key: &str) -> anyhow::Result<()> {
+        let key: [u8; 32] = Self::get_hex(key)
+            .and_then(|key| key.try_into().ok())
+            .ok_or_else(|| anyhow::anyhow!("Invalid public key, for archive signature."))?;
+        let key = ed25519_dalek::VerifyingKey::from_bytes(&key)?;
+
+        let file = Self::get_path(path);
+        let signature = std::fs::read_to_string(&file).map_err(|error| {
+            anyhow::anyhow!(
+                "Error reading signature \"{}\" for archive \"{}\": {error}",
+                file.display(),
+                path.display()
+            )
+        })?;
+        let signature: [u8; 64] = Self::get_hex(signature.trim())
+            .and_then(|signature| signature.try_into().ok())
+            .ok_or_else(|| anyhow::anyhow!("Invalid signature \"{}\".", file.display()))?;
+
+        key.verify_strict(data, &ed25519_dalek::Signature::from_bytes(&signature))
+            .map_err(|_| {
+                anyhow::anyhow!(
+                    "Archive \"{}\" does not match its signature, and may have been modified.",
+                    path.display()
+                )
+            })
+    }
+}
+
+//================================================================
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const SECRET_KEY: &str = "9d61b19deffd5a60ba844af492ec2cc44449c5697b326919703bac031cae7f60";
+
+    // Temporary folder of a single test, removed along with every file in it once the test is over.
+    struct Folder(PathBuf);
+
+    impl Drop for Folder {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+
+    // Sign data, and write it to a temporary archive with its signature next to it. Return the archive folder, the
+    // archive path, and the public key.
+    fn get_archive(name: &str, data: &[u8]) -> (Folder, PathBuf, String) {
+        let folder =
+            std::env::temp_dir().join(format!("flak_signature_{}_{name}", std::process::id()));
+        std::fs::create_dir_all(&folder).unwrap();
+
+        let path = folder.join("archive");
+        let (signature, public) = Signature::sign(data, SECRET_KEY).unwrap();
+
+        std::fs::write(&path, data).unwrap();
+        std::fs::write(Signature::get_path(&path), format!("{signature}\n")).unwrap();
+
+        (Folder(folder), path, public)
+    }
+
+    #[test]
+    fn get_hex() {
+        assert_eq!(Signature::get_hex("00ff7a").unwrap(), [0x00, 0xFF, 0x7A]);
+        assert_eq!(Signature::to_hex(&[0x00, 0xFF, 0x7A]), "00ff7a");
+        assert!(Signature::get_hex("abc").is_none());
+        assert!(Signature::get_hex("zz").is_none());
+    }
+
+    #[test]
+    fn sign_verify() {
+        let data = b"archive data".to_vec();
+        let (_folder, path, public) = get_archive("valid", &data);
+
+        assert!(Signature::verify(&data, &path, &public).is_ok());
+    }
+
+    #[test]
+    fn verify_modified() {
+        let mut data = b"archive data".to_vec();
+        let (_folder, path, public) = get_archive("modified", &data);
+
+        data[0] ^= 1;
+
+        assert!(Signature::verify(&data, &path, &public).is_err());
+    }
+
+    #[test]
+    fn verify_invalid() {
+        let data = b"archive data".to_vec();
+        let (_folder, path, public) = get_archive("invalid", &data);
+
+        // Another key, or a missing signature.
+        let (_, other) = Signature::sign(&data, &"01".repeat(32)).unwrap();
+        assert!(Signature::verify(&data, &path, &other).is_err());
+        assert!(Signature::verify(&data, &path.with_extension("none"), &public).is_err());
+        assert!(Signature::sign(&data, "00").is_err());
+    }
+}
@@ -1,12 +1,9 @@
 use darling::FromMeta;
 use proc_macro::TokenStream;
-use quote::quote;
+use quote::{ToTokens, quote};
+use serde_json::json;
 use std::fmt::Display;
-use syn::{ItemFn, ItemStruct};
-
-//================================================================
-
-const META_PATH: &str = "engine_macro/out";
+use syn::{ItemEnum, ItemFn, ItemImpl, ItemStruct};
 
 //================================================================
 
@@ -19,16 +16,107 @@ struct Function {
     parameter: Vec<Value>,
     #[darling(multiple)]
     result: Vec<Value>,
+    // Generic type names, such as "T", for use with a "generic" kind.
+    #[darling(multiple)]
+    generic: Vec<String>,
+    // Every other shape of the function, such as a shape with a different result for a different argument.
+    #[darling(multiple)]
+    overload: Vec<Overload>,
+    // Lua operator (such as "add" for "__add") of a method, which is set as a meta-method of its class. A method
+    // taking its left operand by value (such as "mlua::Value") rather than by reference is set as a meta-function
+    // instead, so that its class may be on either side (such as "2 * vector").
+    operator: Option<String>,
+    // Set by the "export" attribute of an impl block, which will export the function itself.
+    #[darling(default)]
+    export: bool,
 }
 
 impl Function {
-    fn write(&self, name: &str, method: bool) {
+    // Every operator, with its meta-method.
+    const OPERATOR: [(&str, &str); 16] = [
+        ("add", "Add"),
+        ("sub", "Sub"),
+        ("mul", "Mul"),
+        ("div", "Div"),
+        ("mod", "Mod"),
+        ("pow", "Pow"),
+        ("unm", "Unm"),
+        ("concat", "Concat"),
+        ("len", "Len"),
+        ("eq", "Eq"),
+        ("lt", "Lt"),
+        ("le", "Le"),
+        ("call", "Call"),
+        ("tostring", "ToString"),
+        ("index", "Index"),
+        ("newindex", "NewIndex"),
+    ];
+    // Every operator with a Lua language server annotation.
+    const OPERATOR_META: [&str; 10] = [
+        "add", "sub", "mul", "div", "mod", "pow", "unm", "concat", "len", "call",
+    ];
+
+    fn get_meta(&self, name: &str, method: bool) -> proc_macro2::TokenStream {
         let mut buffer = String::new();
 
+        // An operator is part of its class, not a function. Only an operator with an annotation is written.
+        if let Some(operator) = &self.operator {
+            if Self::OPERATOR_META.contains(&operator.as_str()) {
+                let parameter: Vec<String> = self
+                    .parameter
+                    .iter()
+                    .map(|parameter| parameter.kind.to_string())
+                    .collect();
+                let parameter = if parameter.is_empty() {
+                    String::new()
+                } else {
+                    format!("({})", parameter.join(", "))
+                };
+                let result: Vec<String> = self.result.iter().map(Value::get_kind).collect();
+
+                buffer.push_str(&format!(
+                    "---@class {}\n---@operator {operator}{parameter}: {}",
+                    self.from,
+                    result.join(", ")
+                ));
+            }
+        } else {
+            self.push_meta(&mut buffer, name, method);
+        }
+
+        let path = if method {
+            format!("function_{}.{name}", self.from)
+        } else {
+            format!("function_flak.{}.{name}", self.from)
+        };
+
+        get_submit(
+            &path,
+            &buffer,
+            &json!({
+                "kind": "function",
+                "from": self.from,
+                "name": name,
+                "method": method,
+                "info": self.info,
+                "parameter": self.parameter.iter().map(Value::get_json).collect::<Vec<_>>(),
+                "result": self.result.iter().map(Value::get_json).collect::<Vec<_>>(),
+                "generic": self.generic,
+                "overload": self.overload.iter().map(Overload::get_json).collect::<Vec<_>>(),
+                "operator": self.operator,
+            }),
+        )
+    }
+
+    fn push_meta(&self, buffer: &mut String, name: &str, method: bool) {
         for line in self.info.lines() {
             buffer.push_str(&format!("---{}\n", line.trim()));
         }
 
+        if !self.generic.is_empty() {
+            buffer.push_str(&format!("---@generic {}\n", self.generic.join(", ")));
+        }
+
         let mut buffer_parameter = String::new();
 
         for (i, parameter) in self.parameter.iter().enumerate() {
@@ -55,6 +143,13 @@ impl Function {
             ));
         }
 
+        for overload in &self.overload {
+            buffer.push_str(&format!(
+                "---@overload {}\n",
+                overload.get_kind(method.then_some(&self.from))
+            ));
+        }
+
         let from = if method {
             &self.from
         } else {
@@ -65,8 +160,200 @@ impl Function {
         buffer.push_str(&format!(
             "function {from}{method}{name}({buffer_parameter}) end\n",
         ));
+    }
+}
+
+impl Function {
+    // Register a function, to be set in its module table by "Export::set_table".
+    fn get_export(
+        &self,
+        signature: &syn::Signature,
+        path: proc_macro2::TokenStream,
+    ) -> proc_macro2::TokenStream {
+        let from = &self.from;
+        let name = signature.ident.to_string();
+        let asynchronous = signature.asyncness.is_some();
+        let create = if asynchronous {
+            quote! { lua.create_async_function(#path) }
+        } else {
+            quote! { lua.create_function(#path) }
+        };
+
+        quote! {
+            inventory::submit! {
+                crate::module::general::Export {
+                    from: #from,
+                    name: #name,
+                    call: |lua| #create,
+                    asynchronous: #asynchronous,
+                }
+            }
+        }
+    }
+
+    // Replace the argument of a function with the Lua argument list, and convert it with a check against the
+    // "parameter" meta-data. The signature of the function must match the "parameter" meta-data.
+    fn get_wrapper(&self, mut input: ItemFn, method: bool) -> syn::Result<ItemFn> {
+        let from = if method {
+            format!("{}:{}", self.from, input.sig.ident)
+        } else {
+            format!("{}.{}", self.from, input.sig.ident)
+        };
+
+        let Some(syn::FnArg::Typed(argument)) = input.sig.inputs.last_mut() else {
+            return Err(syn::Error::new_spanned(
+                &input.sig,
+                "A function must have an argument.",
+            ));
+        };
+
+        let pattern = argument.pat.clone();
+        let kind = argument.ty.clone();
+        let element: Vec<syn::Type> = match &*kind {
+            syn::Type::Tuple(tuple) => tuple.elems.iter().cloned().collect(),
+            kind => vec![kind.clone()],
+        };
+
+        if element.len() != self.parameter.len() {
+            return Err(syn::Error::new_spanned(
+                &kind,
+                format!(
+                    "The argument count ({}) does not match the \"parameter\" count ({}).",
+                    element.len(),
+                    self.parameter.len()
+                ),
+            ));
+        }
+
+        // The last argument will take every remaining value, if it is a multi-value.
+        let variadic = element.last().is_some_and(|kind| {
+            if let syn::Type::Path(path) = kind
+                && let Some(segment) = path.path.segments.last()
+            {
+                segment.ident == "MultiValue" || segment.ident == "Variadic"
+            } else {
+                false
+            }
+        });
+
+        // Only the last parameter can be variadic (named "..."), and only if it takes every remaining value.
+        for (i, parameter) in self.parameter.iter().enumerate() {
+            let last = i == self.parameter.len() - 1;
+
+            if parameter.name == "..." && !(last && variadic) {
+                return Err(syn::Error::new_spanned(
+                    &kind,
+                    "Only the last parameter can be variadic (\"...\"), with a multi-value argument.",
+                ));
+            }
+
+            if parameter.name != "..." && last && variadic {
+                return Err(syn::Error::new_spanned(
+                    &kind,
+                    "A multi-value argument must have a variadic (\"...\") parameter.",
+                ));
+            }
+        }
+
+        *argument.pat = syn::parse_quote!(argument_list);
+        *argument.ty = syn::parse_quote!(mlua::MultiValue);
+
+        let lua = match input.sig.inputs.first_mut() {
+            Some(syn::FnArg::Typed(lua)) => match &*lua.pat {
+                syn::Pat::Ident(ident) => ident.ident.clone(),
+                _ => {
+                    *lua.pat = syn::parse_quote!(lua);
+                    syn::parse_quote!(lua)
+                }
+            },
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    &input.sig,
+                    "The first argument of a function must be the Lua state.",
+                ));
+            }
+        };
+
+        // Any surplus argument is left unread, as with any Lua function.
+        let mut value = Vec::new();
+
+        for (i, (kind, parameter)) in element.iter().zip(&self.parameter).enumerate() {
+            if variadic && i == element.len() - 1 {
+                value.push(quote! {
+                    <#kind as mlua::FromLuaMulti>::from_lua_multi(argument_list.collect(), &#lua)?
+                });
+            } else {
+                let name = &parameter.name;
+                let info = parameter.kind.get_check();
+                let optional = parameter.optional.unwrap_or(false);
+
+                value.push(quote! {
+                    crate::module::general::get_argument::<#kind>(
+                        &#lua,
+                        #from,
+                        #name,
+                        #info,
+                        #optional,
+                        argument_list.next(),
+                    )?
+                });
+            }
+        }
+
+        let value = match &*kind {
+            syn::Type::Tuple(_) => quote! { (#(#value,)*) },
+            _ => quote! { #(#value)* },
+        };
+
+        let block = &input.block;
+
+        *input.block = syn::parse_quote!({
+            #[allow(unused_mut)]
+            let mut argument_list = argument_list.into_iter();
+            let #pattern: #kind = #value;
+            #block
+        });
+
+        Ok(input)
+    }
+}
+
+//================================================================
+
+#[derive(Debug, FromMeta)]
+struct Overload {
+    #[darling(multiple)]
+    parameter: Vec<Value>,
+    #[darling(multiple)]
+    result: Vec<Value>,
+}
+
+impl Overload {
+    // Get the overload as a function kind, such as "fun(path: string, binary: true): table".
+    fn get_kind(&self, class: Option<&String>) -> String {
+        let mut parameter: Vec<String> = class
+            .map(|class| format!("self: {class}"))
+            .into_iter()
+            .collect();
+
+        for value in &self.parameter {
+            parameter.push(format!("{}: {}", value.get_name(), value.kind));
+        }
+
+        let result: Vec<String> = self.result.iter().map(Value::get_kind).collect();
+
+        if result.is_empty() {
+            format!("fun({})", parameter.join(", "))
+        } else {
+            format!("fun({}): {}", parameter.join(", "), result.join(", "))
+        }
+    }
 
-        write_to_out(&format!("function_{from}.{name}"), &buffer);
+    fn get_json(&self) -> serde_json::Value {
+        json!({
+            "parameter": self.parameter.iter().map(Value::get_json).collect::<Vec<_>>(),
+            "result": self.result.iter().map(Value::get_json).collect::<Vec<_>>(),
+        })
     }
 }
 
@@ -80,7 +367,7 @@ struct Class {
 }
 
 impl Class {
-    fn write(&self, name: &str) {
+    fn get_meta(&self, name: &str) -> proc_macro2::TokenStream {
         let mut buffer = String::new();
         let name = if let Some(name) = &self.name {
             name
@@ -94,7 +381,15 @@ impl Class {
 
         buffer.push_str(&format!("---@class {name}\n{name} = {{}}"));
 
-        write_to_out(&format!("class_{}", name), &buffer);
+        get_submit(
+            &format!("class_{}", name),
+            &buffer,
+            &json!({
+                "kind": "class",
+                "name": name,
+                "info": self.info,
+            }),
+        )
     }
 }
 
@@ -108,7 +403,7 @@ struct Module {
 }
 
 impl Module {
-    fn write(&self) {
+    fn get_meta(&self) -> proc_macro2::TokenStream {
         let mut buffer = String::new();
 
         for line in self.info.lines() {
@@ -117,7 +412,109 @@ impl Module {
 
         buffer.push_str(&format!("flak.{} = {{}}", self.name));
 
-        write_to_out(&format!("module_{}", &self.name), &buffer);
+        get_submit(
+            &format!("module_{}", &self.name),
+            &buffer,
+            &json!({
+                "kind": "module",
+                "name": self.name,
+                "info": self.info,
+            }),
+        )
+    }
+}
+
+//================================================================
+
+#[derive(Debug, FromMeta)]
+#[darling(derive_syn_parse)]
+struct Enumeration {
+    name: Option<String>,
+    info: String,
+}
+
+impl Enumeration {
+    fn get_meta(
+        &self,
+        kind: &str,
+        name: &str,
+        list: &[(String, String, i64)],
+    ) -> proc_macro2::TokenStream {
+        let mut buffer = String::new();
+
+        for line in self.info.lines() {
+            buffer.push_str(&format!("---{}\n", line.trim()));
+        }
+
+        buffer.push_str(&format!("---@enum {kind}\nflak.{name} = {{\n"));
+
+        for (variant, info, value) in list {
+            if !info.is_empty() {
+                buffer.push_str(&format!("    ---{info}\n"));
+            }
+
+            buffer.push_str(&format!("    {variant} = {value},\n"));
+        }
+
+        buffer.push('}');
+
+        get_submit(
+            &format!("enumeration_{kind}"),
+            &buffer,
+            &json!({
+                "kind": "enumeration",
+                "name": kind,
+                "table": name,
+                "info": self.info,
+                "variant": list
+                    .iter()
+                    .map(|(name, info, value)| json!({ "name": name, "info": info, "value": value }))
+                    .collect::<Vec<_>>(),
+            }),
+        )
+    }
+}
+
+//================================================================
+
+#[derive(Debug, FromMeta)]
+#[darling(derive_syn_parse)]
+struct Record {
+    name: Option<String>,
+    info: String,
+    #[darling(multiple)]
+    field: Vec<Value>,
+}
+
+impl Record {
+    fn get_meta(&self, name: &str, field: &[Value]) -> proc_macro2::TokenStream {
+        let mut buffer = String::new();
+
+        for line in self.info.lines() {
+            buffer.push_str(&format!("---{}\n", line.trim()));
+        }
+
+        buffer.push_str(&format!("---@class {name}\n"));
+
+        for field in field {
+            buffer.push_str(&format!(
+                "---@field {} {} # {}\n",
+                field.get_name(),
+                field.kind,
+                field.info
+            ));
+        }
+
+        get_submit(
+            &format!("record_{name}"),
+            &buffer,
+            &json!({
+                "kind": "record",
+                "name": name,
+                "info": self.info,
+                "field": field.iter().map(Value::get_json).collect::<Vec<_>>(),
+            }),
+        )
     }
 }
 
@@ -127,6 +524,8 @@ impl Module {
 #[darling(derive_syn_parse)]
 struct Value {
     name: String,
+    // Not needed for an overload, which is documented by its function.
+    #[darling(default)]
     info: String,
     kind: ValueKind,
     optional: Option<bool>,
@@ -143,6 +542,15 @@ impl Value {
         }
     }
 
+    fn get_json(&self) -> serde_json::Value {
+        json!({
+            "name": self.name,
+            "info": self.info,
+            "kind": self.kind.to_string(),
+            "optional": self.optional.unwrap_or(false),
+        })
+    }
+
     fn get_kind(&self) -> String {
         if let Some(optional) = self.optional
             && optional
@@ -161,6 +569,7 @@ enum ValueKind {
     Boolean,
     Function,
     Table,
+    Any,
     #[darling(rename = "Vector2")]
     Vector2,
     #[darling(rename = "Vector3")]
@@ -178,6 +587,72 @@ enum ValueKind {
     UserData {
         name: String,
     },
+    // Any of a list of kinds, such as "table|string".
+    Union {
+        #[darling(multiple)]
+        kind: Vec<ValueKind>,
+    },
+    // A literal value, such as "true", for use in an overload.
+    Literal {
+        value: String,
+    },
+    // A generic type name, from the "generic" list of its function.
+    Generic {
+        name: String,
+    },
+    // A function, with its result kind list, such as "fun(...): T".
+    Call {
+        #[darling(multiple)]
+        result: Vec<ValueKind>,
+    },
+}
+
+impl ValueKind {
+    // Get the kind of a Rust type, as given to (or taken from) Lua.
+    fn from_type(kind: &syn::Type) -> Self {
+        let name = if let syn::Type::Path(path) = kind
+            && let Some(segment) = path.path.segments.last()
+        {
+            segment.ident.to_string()
+        } else {
+            return Self::Table;
+        };
+
+        match name.as_str() {
+            "String" | "str" => Self::String,
+            "bool" => Self::Boolean,
+            "f32" | "f64" | "i8" | "i16" | "i32" | "i64" | "u8" | "u16" | "u32" | "u64"
+            | "usize" | "isize" => Self::Number,
+            "Vector2" => Self::Vector2,
+            "Vector3" => Self::Vector3,
+            "Box2" => Self::Box2,
+            "Box3" => Self::Box3,
+            "Camera2D" => Self::Camera2D,
+            "Camera3D" => Self::Camera3D,
+            "Color" => Self::Color,
+            "Vec" | "HashMap" | "BTreeMap" => Self::Table,
+            _ => Self::UserData { name },
+        }
+    }
+
+    // Get the kind to check an argument against, which is "any" for a generic kind.
+    fn get_check(&self) -> String {
+        match self {
+            ValueKind::Union { kind } => kind
+                .iter()
+                .map(Self::get_check)
+                .collect::<Vec<_>>()
+                .join("|"),
+            ValueKind::Literal { value } => match value.as_str() {
+                "true" | "false" => "boolean".to_string(),
+                value if value.starts_with('"') => "string".to_string(),
+                _ => "number".to_string(),
+            },
+            ValueKind::Generic { .. } => "any".to_string(),
+            ValueKind::Call { .. } => "function".to_string(),
+            kind => kind.to_string(),
+        }
+    }
 }
 
 impl Display for ValueKind {
@@ -188,6 +663,7 @@ impl Display for ValueKind {
             ValueKind::Boolean => f.write_str("boolean"),
             ValueKind::Function => f.write_str("function"),
             ValueKind::Table => f.write_str("table"),
+            ValueKind::Any => f.write_str("any"),
             ValueKind::Vector2 => f.write_str("Vector2"),
             ValueKind::Vector3 => f.write_str("Vector3"),
             ValueKind::Box2 => f.write_str("Box2"),
@@ -196,6 +672,25 @@ impl Display for ValueKind {
             ValueKind::Camera3D => f.write_str("Camera3D"),
             ValueKind::Color => f.write_str("Color"),
             ValueKind::UserData { name } => f.write_str(name),
+            ValueKind::Union { kind } => f.write_str(
+                &kind
+                    .iter()
+                    .map(ValueKind::to_string)
+                    .collect::<Vec<_>>()
+                    .join("|"),
+            ),
+            ValueKind::Literal { value } => f.write_str(value),
+            ValueKind::Generic { name } => f.write_str(name),
+            ValueKind::Call { result } if result.is_empty() => f.write_str("fun(...)"),
+            ValueKind::Call { result } => write!(
+                f,
+                "fun(...): {}",
+                result
+                    .iter()
+                    .map(ValueKind::to_string)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
         }
     }
 }
@@ -204,41 +699,187 @@ impl Display for ValueKind {
 
 #[proc_macro_attribute]
 pub fn function(argument_list: TokenStream, input: TokenStream) -> TokenStream {
-    let function: Function = match syn::parse(argument_list) {
-        Ok(v) => v,
-        Err(e) => {
-            return e.to_compile_error().into();
+    get_function(argument_list, input, false)
+}
+
+#[proc_macro_attribute]
+pub fn method(argument_list: TokenStream, input: TokenStream) -> TokenStream {
+    get_function(argument_list, input, true)
+}
+
+// Export every function and method in an impl block. Methods are set in a generated "mlua::UserData" implementation.
+#[proc_macro_attribute]
+pub fn export(_: TokenStream, input: TokenStream) -> TokenStream {
+    let mut input = syn::parse_macro_input!(input as ItemImpl);
+    let kind = input.self_ty.clone();
+    let mut export = Vec::new();
+    let mut method = Vec::new();
+    let mut meta = Vec::new();
+
+    for item in &mut input.items {
+        let syn::ImplItem::Fn(item) = item else {
+            continue;
+        };
+
+        for attribute in &mut item.attrs {
+            let is_method = attribute.path().is_ident("method");
+
+            if !is_method && !attribute.path().is_ident("function") {
+                continue;
+            }
+
+            let function: Function = match attribute.parse_args() {
+                Ok(v) => v,
+                Err(e) => {
+                    return e.to_compile_error().into();
+                }
+            };
+
+            if let syn::Meta::List(list) = &mut attribute.meta {
+                list.tokens.extend(quote! { , export = true });
+            }
+
+            let name = &item.sig.ident;
+            let name_text = name.to_string();
+
+            // An item can not be registered within an impl block, so it is registered after it.
+            meta.push(function.get_meta(&name_text, is_method));
+
+            if is_method {
+                let mutable = matches!(
+                    item.sig.inputs.iter().nth(1),
+                    Some(syn::FnArg::Typed(this)) if matches!(&*this.ty, syn::Type::Reference(kind) if kind.mutability.is_some())
+                );
+                let reference = matches!(
+                    item.sig.inputs.iter().nth(1),
+                    Some(syn::FnArg::Typed(this)) if matches!(&*this.ty, syn::Type::Reference(_))
+                );
+
+                if let Some(operator) = &function.operator {
+                    let Some((_, meta_method)) =
+                        Function::OPERATOR.iter().find(|(name, _)| name == operator)
+                    else {
+                        return syn::Error::new_spanned(
+                            &item.sig.ident,
+                            format!("Unknown operator \"{operator}\"."),
+                        )
+                        .to_compile_error()
+                        .into();
+                    };
+
+                    if item.sig.asyncness.is_some() {
+                        return syn::Error::new_spanned(
+                            &item.sig.ident,
+                            "An operator can not be asynchronous.",
+                        )
+                        .to_compile_error()
+                        .into();
+                    }
+
+                    let meta_method = syn::Ident::new(meta_method, proc_macro2::Span::call_site());
+
+                    if !reference {
+                        method.push(quote! {
+                            method.add_meta_function(
+                                mlua::MetaMethod::#meta_method,
+                                |lua, (this, argument_list): (mlua::Value, mlua::MultiValue)| {
+                                    Self::#name(lua, this, argument_list)
+                                },
+                            );
+                        });
+
+                        continue;
+                    }
+
+                    let add = if mutable {
+                        quote! { add_meta_method_mut }
+                    } else {
+                        quote! { add_meta_method }
+                    };
+
+                    method.push(quote! {
+                        method.#add(mlua::MetaMethod::#meta_method, Self::#name);
+                    });
+
+                    continue;
+                }
+
+                let add = match (item.sig.asyncness.is_some(), mutable) {
+                    (false, false) => quote! { add_method },
+                    (false, true) => quote! { add_method_mut },
+                    (true, false) => quote! { add_async_method },
+                    (true, true) => quote! { add_async_method_mut },
+                };
+
+                // Every method is run in a profile zone, as with every function. An asynchronous method would
+                // close its zone on its first yield, so it is set as is.
+                if item.sig.asyncness.is_some() {
+                    method.push(quote! {
+                        method.#add(#name_text, Self::#name);
+                    });
+                } else {
+                    let zone = format!("{}:{name_text}", function.from);
+
+                    method.push(quote! {
+                        method.#add(#name_text, |lua, this, argument_list: mlua::MultiValue| {
+                            crate::module::profile::Profile::scope(lua, #zone, || {
+                                Self::#name(lua, this, argument_list)
+                            })
+                        });
+                    });
+                }
+            } else {
+                export.push(function.get_export(&item.sig, quote! { <#kind>::#name }));
+            }
         }
-    };
-    let input = syn::parse_macro_input!(input as ItemFn);
+    }
 
-    function.write(&input.sig.ident.to_string(), false);
+    let user_data = if method.is_empty() {
+        quote! {}
+    } else {
+        quote! {
+            impl mlua::UserData for #kind {
+                fn add_methods<M: mlua::UserDataMethods<Self>>(method: &mut M) {
+                    #(#method)*
+                }
+            }
+        }
+    };
 
     TokenStream::from(quote! {
         #input
+        #user_data
+        #(#export)*
+        #(#meta)*
     })
 }
 
 #[proc_macro_attribute]
-pub fn method(argument_list: TokenStream, input: TokenStream) -> TokenStream {
-    let function: Function = match syn::parse(argument_list) {
+pub fn class(argument_list: TokenStream, input: TokenStream) -> TokenStream {
+    let class: Class = match syn::parse(argument_list) {
         Ok(v) => v,
         Err(e) => {
             return e.to_compile_error().into();
         }
     };
-    let input = syn::parse_macro_input!(input as ItemFn);
-
-    function.write(&input.sig.ident.to_string(), true);
+    let input = syn::parse_macro_input!(input as ItemStruct);
+    let meta = class.get_meta(&input.ident.to_string());
 
     TokenStream::from(quote! {
         #input
+        #meta
     })
 }
 
 #[proc_macro_attribute]
-pub fn class(argument_list: TokenStream, input: TokenStream) -> TokenStream {
-    let class: Class = match syn::parse(argument_list) {
+pub fn module(argument_list: TokenStream, input: TokenStream) -> TokenStream {
+    TokenStream::from(get_module(argument_list.into(), input.into()))
+}
+
+// Document a table type, such as "Box2". The field list is taken from the struct.
+#[proc_macro_attribute]
+pub fn record(argument_list: TokenStream, input: TokenStream) -> TokenStream {
+    let record: Record = match syn::parse(argument_list) {
         Ok(v) => v,
         Err(e) => {
             return e.to_compile_error().into();
@@ -246,34 +887,552 @@ pub fn class(argument_list: TokenStream, input: TokenStream) -> TokenStream {
     };
     let input = syn::parse_macro_input!(input as ItemStruct);
 
-    class.write(&input.ident.to_string());
+    let syn::Fields::Named(field) = &input.fields else {
+        return syn::Error::new_spanned(input, "A record must have named fields.")
+            .to_compile_error()
+            .into();
+    };
+
+    let field: Vec<Value> = field.named.iter().map(get_field).collect();
+    let name = record.name.clone().unwrap_or(input.ident.to_string());
+    let meta = record.get_meta(&name, &field);
 
     TokenStream::from(quote! {
         #input
+        #meta
     })
 }
 
-#[proc_macro_attribute]
-pub fn module(argument_list: TokenStream, input: TokenStream) -> TokenStream {
-    let module: Module = match syn::parse(argument_list) {
+// Document a table type from another crate, such as "Vector2", with its name and its field list given as is.
+#[proc_macro]
+pub fn foreign_record(argument_list: TokenStream) -> TokenStream {
+    let record: Record = match syn::parse(argument_list) {
         Ok(v) => v,
         Err(e) => {
             return e.to_compile_error().into();
         }
     };
 
-    module.write();
+    let Some(name) = record.name.clone() else {
+        return syn::Error::new(
+            proc_macro2::Span::call_site(),
+            "A foreign record must have a name.",
+        )
+        .to_compile_error()
+        .into();
+    };
+
+    TokenStream::from(record.get_meta(&name, &record.field))
+}
+
+#[proc_macro_attribute]
+pub fn enumeration(argument_list: TokenStream, input: TokenStream) -> TokenStream {
+    TokenStream::from(get_enumeration(argument_list.into(), input.into()))
+}
+
+// Get the doc-comment of an item, as a single line.
+fn get_document(attribute: &[syn::Attribute]) -> String {
+    let mut list = Vec::new();
+
+    for attribute in attribute {
+        if attribute.path().is_ident("doc")
+            && let syn::Meta::NameValue(value) = &attribute.meta
+            && let syn::Expr::Lit(syn::ExprLit {
+                lit: syn::Lit::Str(text),
+                ..
+            }) = &value.value
+        {
+            list.push(text.value().trim().to_string());
+        }
+    }
+
+    list.join(" ")
+}
+
+// Get the meta-data of a record field. An "Option" field is optional.
+fn get_field(field: &syn::Field) -> Value {
+    let mut kind = &field.ty;
+    let mut optional = false;
+
+    if let syn::Type::Path(path) = kind
+        && let Some(segment) = path.path.segments.last()
+        && segment.ident == "Option"
+        && let syn::PathArguments::AngleBracketed(argument) = &segment.arguments
+        && let Some(syn::GenericArgument::Type(inner)) = argument.args.first()
+    {
+        kind = inner;
+        optional = true;
+    }
+
+    Value {
+        name: field
+            .ident
+            .as_ref()
+            .map(|name| name.to_string())
+            .unwrap_or_default(),
+        info: get_document(&field.attrs),
+        kind: ValueKind::from_type(kind),
+        optional: Some(optional),
+    }
+}
+
+// Get the name of every table set with "Export::set_table" in a token stream, such as "input.board".
+fn get_table_list(input: proc_macro2::TokenStream, list: &mut Vec<String>) {
+    let mut token = input.into_iter().peekable();
 
-    input
+    while let Some(tree) = token.next() {
+        match tree {
+            proc_macro2::TokenTree::Ident(name) if name == "set_table" => {
+                if let Some(proc_macro2::TokenTree::Group(group)) = token.peek() {
+                    for tree in group.stream() {
+                        if let proc_macro2::TokenTree::Literal(literal) = tree
+                            && let Ok(text) =
+                                syn::parse2::<syn::LitStr>(literal.into_token_stream())
+                        {
+                            list.push(text.value());
+                        }
+                    }
+                }
+            }
+            proc_macro2::TokenTree::Group(group) => get_table_list(group.stream(), list),
+            _ => {}
+        }
+    }
 }
 
-fn write_to_out(path: &str, data: &str) {
-    if !std::fs::exists(self::META_PATH)
-        .expect("Unable to check the existence of the \"out\" folder.")
+// Expand the "module" attribute, on a proc-macro 2 token stream so that an expansion can be tested.
+fn get_module(
+    argument_list: proc_macro2::TokenStream,
+    input: proc_macro2::TokenStream,
+) -> proc_macro2::TokenStream {
+    let module: Module = match syn::parse2(argument_list) {
+        Ok(v) => v,
+        Err(e) => {
+            return e.to_compile_error();
+        }
+    };
+
+    let meta = module.get_meta();
+
+    // Every function exported from a module is only set if its table is given to "Export::set_table", so a module
+    // with no such call would drop every function in it. A parent module, such as "input" for "input.board",
+    // only needs a call for a child module.
+    let mut table = Vec::new();
+    get_table_list(input.clone(), &mut table);
+
+    let parent = format!("{}.", module.name);
+
+    if !table
+        .iter()
+        .any(|name| *name == module.name || name.starts_with(&parent))
     {
-        std::fs::create_dir(self::META_PATH).expect("Unable to create the \"out\" folder.")
+        return syn::Error::new(
+            proc_macro2::Span::call_site(),
+            format!(
+                "Module \"{}\" is never set with \"Export::set_table\", so none of its functions would be exported.",
+                module.name
+            ),
+        )
+        .to_compile_error();
+    }
+
+    quote! {
+        #input
+        #meta
     }
+}
+
+// Expand the "enumeration" attribute, on a proc-macro 2 token stream so that an expansion can be tested.
+fn get_enumeration(
+    argument_list: proc_macro2::TokenStream,
+    input: proc_macro2::TokenStream,
+) -> proc_macro2::TokenStream {
+    let enumeration: Enumeration = match syn::parse2(argument_list) {
+        Ok(v) => v,
+        Err(e) => {
+            return e.to_compile_error();
+        }
+    };
+    let input: ItemEnum = match syn::parse2(input) {
+        Ok(v) => v,
+        Err(e) => {
+            return e.to_compile_error();
+        }
+    };
+
+    // Every variant must be a unit variant, with an optional integer literal discriminant.
+    let mut list: Vec<(String, String, i64)> = Vec::new();
+    let mut value = 0;
 
-    std::fs::write(format!("{}/{}", self::META_PATH, path), data)
-        .expect("Unable to write to \"out\" folder.")
+    for variant in &input.variants {
+        if !matches!(variant.fields, syn::Fields::Unit) {
+            return syn::Error::new_spanned(variant, "An enumeration variant must have no field.")
+                .to_compile_error();
+        }
+
+        if let Some((_, discriminant)) = &variant.discriminant {
+            match discriminant {
+                syn::Expr::Lit(syn::ExprLit {
+                    lit: syn::Lit::Int(literal),
+                    ..
+                }) => match literal.base10_parse::<i64>() {
+                    Ok(literal) => value = literal,
+                    Err(e) => return e.to_compile_error(),
+                },
+                _ => {
+                    return syn::Error::new_spanned(
+                        discriminant,
+                        "An enumeration discriminant must be an integer literal.",
+                    )
+                    .to_compile_error();
+                }
+            }
+        }
+
+        list.push((
+            variant.ident.to_string(),
+            get_document(&variant.attrs),
+            value,
+        ));
+
+        value += 1;
+    }
+
+    let kind = input.ident.to_string();
+    let name = enumeration.name.clone().unwrap_or(kind.clone());
+
+    let meta = enumeration.get_meta(&kind, &name, &list);
+
+    let ident = &input.ident;
+    let variant: Vec<&syn::Ident> = input
+        .variants
+        .iter()
+        .map(|variant| &variant.ident)
+        .collect();
+    let count = variant.len();
+    let error_write = format!("flak.{name} is read-only.");
+    let error_value = format!("Invalid {kind} value: {{}}.");
+
+    quote! {
+        #input
+        #meta
+
+        impl #ident {
+            pub const LIST: [Self; #count] = [#(Self::#variant),*];
+
+            // Set a read-only table with every variant, as "flak.name".
+            pub fn set_global(lua: &mlua::Lua, global: &mlua::Table) -> mlua::Result<()> {
+                let data = lua.create_table()?;
+
+                #(data.set(stringify!(#variant), Self::#variant as mlua::Integer)?;)*
+
+                let meta = lua.create_table()?;
+                meta.set("__index", &data)?;
+                meta.set(
+                    "__newindex",
+                    lua.create_function(|_, _: mlua::MultiValue| -> mlua::Result<()> {
+                        Err(mlua::Error::runtime(#error_write))
+                    })?,
+                )?;
+                meta.set(
+                    "__pairs",
+                    lua.create_function(move |lua, _: mlua::Value| {
+                        let next: mlua::Function = lua.globals().get("next")?;
+                        Ok((next, data.clone(), mlua::Nil))
+                    })?,
+                )?;
+                meta.set("__metatable", false)?;
+
+                let table = lua.create_table()?;
+                table.set_metatable(Some(meta))?;
+
+                global.set(#name, table)
+            }
+        }
+
+        impl mlua::IntoLua for #ident {
+            fn into_lua(self, _: &mlua::Lua) -> mlua::Result<mlua::Value> {
+                Ok(mlua::Value::Integer(self as mlua::Integer))
+            }
+        }
+
+        impl mlua::FromLua for #ident {
+            fn from_lua(value: mlua::Value, lua: &mlua::Lua) -> mlua::Result<Self> {
+                let value = <mlua::Integer as mlua::FromLua>::from_lua(value, lua)?;
+
+                Self::LIST
+                    .into_iter()
+                    .find(|variant| *variant as mlua::Integer == value)
+                    .ok_or_else(|| mlua::Error::runtime(format!(#error_value, value)))
+            }
+        }
+    }
+}
+
+fn get_function(argument_list: TokenStream, input: TokenStream, method: bool) -> TokenStream {
+    let function: Function = match syn::parse(argument_list) {
+        Ok(v) => v,
+        Err(e) => {
+            return e.to_compile_error().into();
+        }
+    };
+    let input = syn::parse_macro_input!(input as ItemFn);
+
+    // A function within an impl block, or any method, can only be exported (and registered) by the "export"
+    // attribute of its impl block.
+    let export = if function.export {
+        quote! {}
+    } else if method {
+        syn::Error::new_spanned(
+            &input.sig.ident,
+            format!(
+                "The method \"{}:{}\" is not exported. Its impl block must have the \"export\" attribute.",
+                function.from, input.sig.ident
+            ),
+        )
+        .to_compile_error()
+    } else {
+        let name = &input.sig.ident;
+        let export = function.get_export(&input.sig, quote! { self::#name });
+        let meta = function.get_meta(&name.to_string(), method);
+
+        quote! {
+            #export
+            #meta
+        }
+    };
+
+    // Keep the original function on error, to only report the error itself.
+    match function.get_wrapper(input.clone(), method) {
+        Ok(v) => TokenStream::from(quote! {
+            #v
+            #export
+        }),
+        Err(e) => {
+            let e = e.to_compile_error();
+
+            TokenStream::from(quote! {
+                #input
+                #e
+            })
+        }
+    }
+}
+
+// Register the meta-data of an item, to be written by the "meta" command.
+fn get_submit(path: &str, meta: &str, json: &serde_json::Value) -> proc_macro2::TokenStream {
+    let meta = meta.trim();
+    let json = serde_json::to_string(json).expect("Unable to serialize description.");
+
+    quote! {
+        inventory::submit! {
+            crate::meta::Meta {
+                path: #path,
+                meta: #meta,
+                json: #json,
+            }
+        }
+    }
+}
+
+//================================================================
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // Get the text of a token stream, without any white-space, as the spacing of "to_string" is not stable.
+    fn get_text(token: impl ToTokens) -> String {
+        token
+            .to_token_stream()
+            .to_string()
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .collect()
+    }
+
+    fn get_wrapper(
+        function: proc_macro2::TokenStream,
+        input: proc_macro2::TokenStream,
+    ) -> syn::Result<String> {
+        let function: Function = syn::parse2(function)?;
+
+        function
+            .get_wrapper(syn::parse2(input)?, false)
+            .map(get_text)
+    }
+
+    #[test]
+    fn wrapper_optional() {
+        let text = get_wrapper(
+            quote! {
+                from = "test",
+                info = "Test.",
+                parameter(name = "a", info = "A.", kind = "number"),
+                parameter(name = "b", info = "B.", kind = "string", optional = true)
+            },
+            quote! {
+                fn call(lua: &mlua::Lua, (a, b): (f32, Option<String>)) -> mlua::Result<()> {
+                    Ok(())
+                }
+            },
+        )
+        .unwrap();
+
+        // Every argument is taken from a multi-value, one at a time, and checked against its parameter.
+        assert!(text.contains("argument_list:mlua::MultiValue"));
+        assert!(text.contains(
+            r#"get_argument::<f32>(&lua,"test.call","a","number",false,argument_list.next(),)?"#
+        ));
+        assert!(text.contains(
+            r#"get_argument::<Option<String>>(&lua,"test.call","b","string",true,argument_list.next(),)?"#
+        ));
+        // A surplus argument is left unread.
+        assert!(!text.contains("check_argument"));
+    }
+
+    #[test]
+    fn wrapper_variadic() {
+        let text = get_wrapper(
+            quote! {
+                from = "test",
+                info = "Test.",
+                parameter(name = "a", info = "A.", kind = "number"),
+                parameter(name = "...", info = "Rest.", kind = "string")
+            },
+            quote! {
+                fn call(lua: &mlua::Lua, (a, rest): (f32, mlua::Variadic<String>)) -> mlua::Result<()> {
+                    Ok(())
+                }
+            },
+        )
+        .unwrap();
+
+        // The last argument takes every remaining value.
+        assert!(text.contains(
+            "<mlua::Variadic<String>asmlua::FromLuaMulti>::from_lua_multi(argument_list.collect(),&lua)?"
+        ));
+    }
+
+    #[test]
+    fn wrapper_error() {
+        let function = quote! {
+            from = "test",
+            info = "Test.",
+            parameter(name = "a", info = "A.", kind = "number"),
+            parameter(name = "...", info = "Rest.", kind = "string")
+        };
+
+        // The argument count must match the parameter count.
+        assert!(
+            get_wrapper(
+                function.clone(),
+                quote! { fn call(lua: &mlua::Lua, a: f32) -> mlua::Result<()> { Ok(()) } },
+            )
+            .is_err()
+        );
+        // A variadic parameter must have a multi-value argument.
+        assert!(
+            get_wrapper(
+                function,
+                quote! { fn call(lua: &mlua::Lua, (a, b): (f32, String)) -> mlua::Result<()> { Ok(()) } },
+            )
+            .is_err()
+        );
+        // Only the last parameter can be variadic.
+        assert!(
+            get_wrapper(
+                quote! {
+                    from = "test",
+                    info = "Test.",
+                    parameter(name = "...", info = "Rest.", kind = "string"),
+                    parameter(name = "a", info = "A.", kind = "number")
+                },
+                quote! { fn call(lua: &mlua::Lua, (a, b): (String, f32)) -> mlua::Result<()> { Ok(()) } },
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn enumeration_value() {
+        let text = get_enumeration(
+            quote! { info = "Test." },
+            quote! {
+                enum Kind {
+                    /// A.
+                    A,
+                    B = 4,
+                    C,
+                }
+            },
+        )
+        .to_string();
+
+        // A variant with no discriminant follows the last one.
+        assert!(text.contains(r"flak.Kind = {\n    ---A.\n    A = 0,\n    B = 4,\n    C = 5,\n}"));
+        assert!(
+            get_text(get_enumeration(
+                quote! { info = "Test." },
+                quote! { enum Kind { A } }
+            ))
+            .contains("data.set(stringify!(A),Self::Aasmlua::Integer)?;")
+        );
+    }
+
+    #[test]
+    fn enumeration_error() {
+        let info = quote! { info = "Test." };
+
+        for input in [
+            quote! { enum Kind { A(i32) } },
+            quote! { enum Kind { A = 1 + 1 } },
+        ] {
+            assert!(get_text(get_enumeration(info.clone(), input)).contains("compile_error!"));
+        }
+    }
+
+    #[test]
+    fn module_set_table() {
+        let argument = quote! { name = "test", info = "Test." };
+
+        // A module that never sets its table is a compile error.
+        let text = get_text(get_module(
+            argument.clone(),
+            quote! {
+                pub fn set_global(lua: &mlua::Lua, global: &mlua::Table) -> anyhow::Result<()> {
+                    global.set("test", lua.create_table()?)?;
+                    Ok(())
+                }
+            },
+        ));
+
+        assert!(text.contains("compile_error!"));
+        assert!(text.contains("isneversetwith"));
+
+        let text = get_text(get_module(
+            argument,
+            quote! {
+                pub fn set_global(lua: &mlua::Lua, global: &mlua::Table) -> anyhow::Result<()> {
+                    Export::set_table(lua, "test", &global)?;
+                    Ok(())
+                }
+            },
+        ));
+
+        assert!(!text.contains("compile_error!"));
+
+        // A parent module only needs a child module to be set.
+        let text = get_text(get_module(
+            quote! { name = "input", info = "Input." },
+            quote! {
+                pub fn set_global(lua: &mlua::Lua, global: &mlua::Table) -> anyhow::Result<()> {
+                    Export::set_table(lua, "input.board", &global)?;
+                    Ok(())
+                }
+            },
+        ));
+
+        assert!(!text.contains("compile_error!"));
+    }
 }